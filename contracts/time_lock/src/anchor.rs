@@ -0,0 +1,145 @@
+use crate::errors::Error;
+use crate::storage;
+use crate::types::Settlement;
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec};
+
+/// Registered anchor configuration
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnchorConfig {
+    pub currencies: Vec<String>, // Fiat currency codes the anchor pays out (e.g. "NGN")
+    pub min_payout: i128,        // Minimum post-fee USDC per withdrawal
+    pub max_payout: i128,        // Maximum post-fee USDC per withdrawal
+    pub fee_bps: u32,            // Anchor fee in basis points, paid to the anchor in USDC on confirmation
+    pub enabled: bool,           // Whether new withdrawals may be routed to the anchor
+    pub signing_key: BytesN<32>, // Ed25519 key the anchor signs quotes and confirmations with
+}
+
+/// SEP-38 style firm quote issued by an anchor for one bank withdrawal
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FirmQuote {
+    pub quote_id: String,     // Anchor's quote identifier, usable once
+    pub anchor: Address,      // Anchor that issued the quote
+    pub gift_id: u64,         // Gift being withdrawn
+    pub sell_amount: i128,    // Post-fee USDC the quote covers
+    pub buy_currency: String, // Fiat currency code paid out
+    pub rate: i128,           // Fiat per USDC with precision (e.g., 1000000 = 1.0)
+    pub fee: i128,            // Anchor fee in USDC
+    pub expires_at: u64,      // Ledger timestamp after which the quote is void
+}
+
+/// Firm quote plus the anchor's ed25519 signature over its XDR encoding
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedQuote {
+    pub quote: FirmQuote,
+    pub signature: BytesN<64>,
+}
+
+/// Validate anchor configuration
+pub fn validate_anchor_config(config: &AnchorConfig) -> Result<(), Error> {
+    if config.currencies.is_empty()
+        || config.min_payout <= 0
+        || config.max_payout < config.min_payout
+        || config.fee_bps > 10000
+    {
+        return Err(Error::InvalidAnchorConfig);
+    }
+    Ok(())
+}
+
+/// Load an anchor that is registered, enabled and pays out `currency`
+pub fn require_active_anchor(
+    env: &Env,
+    anchor: &Address,
+    currency: &String,
+) -> Result<AnchorConfig, Error> {
+    let config = storage::get_anchor(env, anchor).ok_or(Error::AnchorNotRegistered)?;
+    if !config.enabled {
+        return Err(Error::AnchorDisabled);
+    }
+    if !config.currencies.contains(currency) {
+        return Err(Error::CurrencyNotSupported);
+    }
+    Ok(config)
+}
+
+/// Validate a payout against the anchor's limits
+pub fn validate_payout_limits(config: &AnchorConfig, amount: i128) -> Result<(), Error> {
+    if amount < config.min_payout || amount > config.max_payout {
+        return Err(Error::PayoutOutsideAnchorLimits);
+    }
+    Ok(())
+}
+
+/// Calculate the anchor's fee on a USDC payout
+pub fn calculate_anchor_fee(config: &AnchorConfig, amount: i128) -> i128 {
+    (amount * config.fee_bps as i128) / 10000
+}
+
+/// Verify the anchor's signature over a settlement confirmation and mark its
+/// transaction ID as used.
+///
+/// The signature covers this contract, the gift and the settlement's nonce,
+/// amount and start time, so it cannot confirm any other settlement.
+pub fn verify_confirmation(
+    env: &Env,
+    config: &AnchorConfig,
+    gift_id: u64,
+    settlement: &Settlement,
+    anchor_tx_id: &String,
+    signature: &BytesN<64>,
+) -> Result<(), Error> {
+    if storage::is_anchor_tx_used(env, anchor_tx_id) {
+        return Err(Error::ConfirmationAlreadyUsed);
+    }
+
+    let mut payload = Bytes::new(env);
+    payload.append(&env.current_contract_address().to_xdr(env));
+    payload.append(&gift_id.to_xdr(env));
+    payload.append(&settlement.nonce.to_xdr(env));
+    payload.append(&settlement.amount_usdc.to_xdr(env));
+    payload.append(&settlement.initiated_at.to_xdr(env));
+    payload.append(&anchor_tx_id.clone().to_xdr(env));
+
+    env.crypto()
+        .ed25519_verify(&config.signing_key, &payload, signature);
+
+    storage::set_anchor_tx_used(env, anchor_tx_id);
+    Ok(())
+}
+
+/// Verify a firm quote for a withdrawal and mark its ID as used
+pub fn verify_quote(
+    env: &Env,
+    config: &AnchorConfig,
+    signed: &SignedQuote,
+    anchor: &Address,
+    gift_id: u64,
+    sell_amount: i128,
+    currency: &String,
+) -> Result<(), Error> {
+    let quote = &signed.quote;
+    if quote.anchor != *anchor
+        || quote.gift_id != gift_id
+        || quote.sell_amount != sell_amount
+        || quote.buy_currency != *currency
+        || quote.fee < 0
+        || quote.fee > sell_amount
+    {
+        return Err(Error::InvalidQuote);
+    }
+    if env.ledger().timestamp() > quote.expires_at {
+        return Err(Error::QuoteExpired);
+    }
+    if storage::is_quote_used(env, &quote.quote_id) {
+        return Err(Error::QuoteAlreadyUsed);
+    }
+
+    env.crypto()
+        .ed25519_verify(&config.signing_key, &quote.clone().to_xdr(env), &signed.signature);
+
+    storage::set_quote_used(env, &quote.quote_id, quote.expires_at);
+    Ok(())
+}
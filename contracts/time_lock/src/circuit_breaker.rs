@@ -0,0 +1,92 @@
+use crate::errors::Error;
+use crate::events::CircuitBreakerTripped;
+use crate::slippage;
+use crate::storage;
+use crate::types::PriceCache;
+use soroban_sdk::{contracttype, symbol_short, Env, String};
+
+/// Circuit breaker configuration for a currency pair
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub max_change_bps: u32, // Maximum rate movement in basis points (1-10000)
+    pub interval: u64,       // Window in seconds over which the movement is measured
+}
+
+/// Validate circuit breaker configuration
+pub fn validate_circuit_breaker_config(config: &CircuitBreakerConfig) -> Result<(), Error> {
+    if config.max_change_bps == 0 || config.max_change_bps > 10000 || config.interval == 0 {
+        return Err(Error::InvalidCircuitBreakerConfig);
+    }
+    Ok(())
+}
+
+/// Movement in basis points of a new oracle reading against the pair's
+/// reference price, if it exceeds the configured limit. Does not touch storage.
+pub fn exceeded_change(env: &Env, currency_pair: &String, new_rate: i128) -> Option<i128> {
+    let config = storage::get_circuit_breaker_config(env, currency_pair)?;
+    let reference = storage::get_reference_price(env, currency_pair)?;
+
+    let change_bps = slippage::calculate_rate_difference(reference.rate, new_rate);
+    if change_bps.abs() <= config.max_change_bps as i128 {
+        return None;
+    }
+
+    Some(change_bps)
+}
+
+/// Check a new oracle reading against the pair's reference price and trip the
+/// breaker if the movement exceeds the configured limit.
+///
+/// The reference is the last accepted reading, so a gap longer than the
+/// interval does not let a broken rate through. An accepted reading becomes
+/// the new reference once the interval since the current one has passed.
+/// While the breaker is tripped the reference is kept for the reset.
+///
+/// Returns `true` if this reading tripped the breaker.
+pub fn check_reading(env: &Env, currency_pair: &String, reading: &PriceCache) -> bool {
+    let Some(config) = storage::get_circuit_breaker_config(env, currency_pair) else {
+        return false;
+    };
+    let reference = storage::get_reference_price(env, currency_pair);
+
+    if let (Some(change_bps), Some(reference)) = (
+        exceeded_change(env, currency_pair, reading.rate),
+        &reference,
+    ) {
+        if !storage::is_circuit_breaker_tripped(env, currency_pair) {
+            storage::set_circuit_breaker_tripped(env, currency_pair, true);
+
+            env.events().publish(
+                (symbol_short!("cb_trip"),),
+                CircuitBreakerTripped {
+                    currency_pair: currency_pair.clone(),
+                    previous_rate: reference.rate,
+                    new_rate: reading.rate,
+                    change_bps,
+                },
+            );
+        }
+        return true;
+    }
+
+    if storage::is_circuit_breaker_tripped(env, currency_pair) {
+        return false;
+    }
+    let window_over = reference.as_ref().is_none_or(|reference| {
+        reading.timestamp.saturating_sub(reference.timestamp) >= config.interval
+    });
+    if window_over {
+        storage::set_reference_price(env, currency_pair, reading);
+    }
+
+    false
+}
+
+/// Fail if the breaker for the pair is tripped
+pub fn ensure_not_tripped(env: &Env, currency_pair: &String) -> Result<(), Error> {
+    if storage::is_circuit_breaker_tripped(env, currency_pair) {
+        return Err(Error::CircuitBreakerTripped);
+    }
+    Ok(())
+}
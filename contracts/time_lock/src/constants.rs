@@ -4,3 +4,27 @@ pub const MAX_GIFT_AMOUNT: i128 = 1_000_000_000;
 pub const MAX_PAYMENT_REF_LENGTH: u32 = 256;
 /// Maximum lock duration: 10 years in seconds (sanity check for data entry errors)
 pub const MAX_LOCK_DURATION: u64 = 10 * 365 * 24 * 60 * 60;
+/// Most installments a recurring gift schedule may have (5 years of monthly gifts)
+pub const MAX_SCHEDULE_INSTALLMENTS: u32 = 60;
+/// Most tranches a milestone gift may be split into
+pub const MAX_GIFT_TRANCHES: u32 = 12;
+/// Most contributors a group gift may have, bounding the refund loop on cancellation
+pub const MAX_GROUP_CONTRIBUTORS: u32 = 50;
+/// Most recipients a split gift may be divided between
+pub const MAX_SPLIT_RECIPIENTS: u32 = 10;
+/// Most claimers (and allowlisted phone hashes) a red envelope may have
+pub const MAX_ENVELOPE_CLAIMERS: u32 = 100;
+/// Times the recipient of an unclaimed gift can be changed
+pub const MAX_RECIPIENT_CHANGES: u32 = 3;
+/// Prefix of the oracle currency pair for a payout currency (e.g. "USDC/NGN")
+pub const CURRENCY_PAIR_PREFIX: &str = "USDC/";
+/// Longest fiat currency code accepted for bank withdrawals, in bytes
+pub const MAX_CURRENCY_CODE_LEN: usize = 12;
+/// How long a DEX swap submitted by the contract stays valid, in seconds
+pub const SWAP_DEADLINE_WINDOW: u64 = 300;
+/// Longest swap path considered during path discovery, in hops
+pub const MAX_PATH_HOPS: u32 = 3;
+/// Cap on registered intermediate assets, bounding the number of router quotes per swap
+pub const MAX_INTERMEDIATE_ASSETS: u32 = 4;
+/// How long an anchor has to confirm a bank settlement before anyone can expire it: 3 days
+pub const SETTLEMENT_TIMEOUT: u64 = 3 * 24 * 60 * 60;
@@ -1,18 +1,36 @@
+use crate::anchor::{self, AnchorConfig, SignedQuote};
+use crate::circuit_breaker::{self, CircuitBreakerConfig};
 use crate::constants;
 use crate::errors::Error;
+use crate::group::{self, GroupGift};
 use crate::events::{
-    AnchorDepositSent, BankWithdrawalInitiated, DepositGiftCreated, FeeWithdrawal, FeesCollected,
-    OracleAddressUpdated, OracleRateQueried, PathPaymentExecuted, SlippageConfigUpdated,
-    WithdrawalSuccess,
+    AnchorDepositSent, AnchorUpdated, BankWithdrawalInitiated, CircuitBreakerReset,
+    CircuitBreakerUpdated, ClaimRelayed, ContributionReceived, DepositGiftCreated, DexRouterUpdated,
+    FeeWithdrawal, FeesCollected, GiftCreated, GiftRejected, GiftScheduleCreated, GiftToppedUp,
+    GroupGiftCancelled, GroupGiftClosed, GroupGiftOpened, InsuranceConfigUpdated,
+    InsuranceReserveFunded, InsuranceReserveWithdrawn, LockedTranchesCancelled,
+    OracleAddressUpdated, OracleRateQueried, PairSlippageUpdated, RecipientUpdated,
+    RedEnvelopeClaimed, RedEnvelopeCreated, RedEnvelopeExhausted, RedEnvelopeRefunded,
+    RelayFeeCapUpdated, RelayerUpdated, SettlementConfirmed, SettlementExpired, SettlementFailed,
+    SlippageConfigUpdated, SplitGiftCreated, SplitSharesRefunded, StalenessPolicyUpdated,
+    TranchedGiftCreated, VestingGiftCreated, WithdrawalSuccess,
 };
-use crate::oracle::{self, OracleConfig};
-use crate::path_payment;
+use crate::insurance::{self, InsuranceConfig};
+use crate::oracle::{self, OracleConfig, StalenessMode};
+use crate::red_envelope::{self, RedEnvelope};
+use crate::schedule::{self, GiftSchedule, ScheduleInstallment, ScheduleStatus};
+use crate::settlement;
 use crate::slippage::{self, SlippageConfig};
+use crate::split::{self, SplitGift, SplitShare};
 use crate::storage;
 use crate::token;
-use crate::types::{Gift, GiftStatus};
+use crate::types::{Gift, GiftKind, GiftStatus, Settlement, Tranche, VestingTerms};
+use crate::tranche;
+use crate::vesting;
+use crate::withdrawal::{self, BankPayout, WithdrawalMethod, WithdrawalPreview};
 use soroban_sdk::{
     contract, contractimpl, symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol,
+    Vec,
 };
 
 #[contract]
@@ -72,9 +90,11 @@ impl TimeLockTrait for TimeLockContract {
             sender: sender.clone(),
             recipient: None,
             amount,
+            withdrawn_amount: 0,
             unlock_timestamp,
             recipient_phone_hash: recipient_phone_hash.clone(),
             status: GiftStatus::Created,
+            kind: GiftKind::Standard,
         };
 
         storage::set_gift(&env, gift_id, &gift);
@@ -109,6 +129,918 @@ impl TimeLockTrait for TimeLockContract {
         Ok(gift_id)
     }
 
+    fn create_vesting_gift(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        terms: VestingTerms,
+        recipient_phone_hash: BytesN<32>,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        if !(constants::MIN_GIFT_AMOUNT..=constants::MAX_GIFT_AMOUNT).contains(&amount) {
+            return Err(Error::InvalidAmount);
+        }
+        vesting::validate_vesting_terms(&env, &terms)?;
+
+        let gift_id = storage::increment_next_gift_id(&env);
+
+        // Claimable from the cliff, when the first part vests
+        let gift = Gift {
+            sender: sender.clone(),
+            recipient: None,
+            amount,
+            withdrawn_amount: 0,
+            unlock_timestamp: terms.cliff,
+            recipient_phone_hash: recipient_phone_hash.clone(),
+            status: GiftStatus::Created,
+            kind: GiftKind::Vesting(terms.clone()),
+        };
+        storage::set_gift(&env, gift_id, &gift);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &sender,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + amount;
+        let total_gifted = storage::get_total_gifted(&env) + amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("vest_new"),),
+            VestingGiftCreated {
+                gift_id,
+                sender,
+                amount,
+                start: terms.start,
+                cliff: terms.cliff,
+                end: terms.end,
+                recipient_hash: recipient_phone_hash,
+            },
+        );
+
+        Ok(gift_id)
+    }
+
+    fn withdraw_vested(env: Env, gift_id: u64) -> Result<i128, Error> {
+        Self::require_recipient_auth(&env, gift_id)?;
+
+        let gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        if !matches!(gift.kind, GiftKind::Vesting(_)) {
+            return Err(Error::InvalidGiftKind);
+        }
+
+        let amount = withdrawal::available_amount(&env, &gift)?;
+        if amount == 0 {
+            return Err(Error::NotUnlocked);
+        }
+        Self::pay_to_wallet(&env, gift_id, Some(amount))?;
+
+        Ok(amount)
+    }
+
+    fn create_tranched_gift(
+        env: Env,
+        sender: Address,
+        tranches: Vec<Tranche>,
+        recipient_phone_hash: BytesN<32>,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        let amount = tranche::validate_tranches(&env, &tranches)?;
+
+        let gift_id = storage::increment_next_gift_id(&env);
+
+        // Claimable from the first unlock
+        let gift = Gift {
+            sender: sender.clone(),
+            recipient: None,
+            amount,
+            withdrawn_amount: 0,
+            unlock_timestamp: tranches.get(0).ok_or(Error::InvalidGiftTerms)?.unlock_timestamp,
+            recipient_phone_hash: recipient_phone_hash.clone(),
+            status: GiftStatus::Created,
+            kind: GiftKind::Tranches(tranches.clone()),
+        };
+        storage::set_gift(&env, gift_id, &gift);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &sender,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + amount;
+        let total_gifted = storage::get_total_gifted(&env) + amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("tr_new"),),
+            TranchedGiftCreated {
+                gift_id,
+                sender,
+                amount,
+                tranches,
+                recipient_hash: recipient_phone_hash,
+            },
+        );
+
+        Ok(gift_id)
+    }
+
+    fn cancel_locked_tranches(env: Env, gift_id: u64) -> Result<i128, Error> {
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        gift.sender.require_auth();
+
+        let GiftKind::Tranches(tranches) = &gift.kind else {
+            return Err(Error::InvalidGiftKind);
+        };
+        if !matches!(
+            gift.status,
+            GiftStatus::Created | GiftStatus::Unlocked | GiftStatus::PendingSettlement
+        ) {
+            return Err(Error::InvalidStatus);
+        }
+
+        let (unlocked, cancelled_tranches, refund_amount) =
+            tranche::split_locked(&env, tranches, env.ledger().timestamp());
+        if cancelled_tranches == 0 {
+            return Err(Error::InvalidGiftTerms);
+        }
+
+        gift.amount -= refund_amount;
+        if unlocked.is_empty() {
+            gift.status = GiftStatus::Refunded;
+        } else if gift.status != GiftStatus::PendingSettlement
+            && withdrawal::remaining_amount(&gift) == 0
+        {
+            // The unlocked tranches were already paid out in full
+            gift.status = GiftStatus::Withdrawn;
+        }
+        gift.kind = GiftKind::Tranches(unlocked);
+        storage::set_gift(&env, gift_id, &gift);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer(&env, &usdc_address, &gift.sender, refund_amount)?;
+
+        let total_held = storage::get_total_held(&env) - refund_amount;
+        let total_gifted = storage::get_total_gifted(&env) - refund_amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("tr_cancel"),),
+            LockedTranchesCancelled {
+                gift_id,
+                sender: gift.sender.clone(),
+                cancelled_tranches,
+                refund_amount,
+                remaining_amount: withdrawal::remaining_amount(&gift),
+            },
+        );
+
+        Ok(refund_amount)
+    }
+
+    fn open_group_gift(
+        env: Env,
+        organizer: Address,
+        unlock_timestamp: u64,
+        funding_deadline: u64,
+        recipient_phone_hash: BytesN<32>,
+    ) -> Result<u64, Error> {
+        organizer.require_auth();
+
+        group::validate_group_gift(&env, unlock_timestamp, funding_deadline)?;
+
+        let gift_id = storage::increment_next_gift_id(&env);
+
+        let gift = Gift {
+            sender: organizer.clone(),
+            recipient: None,
+            amount: 0,
+            withdrawn_amount: 0,
+            unlock_timestamp,
+            recipient_phone_hash: recipient_phone_hash.clone(),
+            status: GiftStatus::Collecting,
+            kind: GiftKind::Standard,
+        };
+        storage::set_gift(&env, gift_id, &gift);
+
+        let group = GroupGift {
+            organizer: organizer.clone(),
+            funding_deadline,
+            contributors: Vec::new(&env),
+        };
+        storage::set_group_gift(&env, gift_id, &group);
+
+        env.events().publish(
+            (symbol_short!("grp_open"),),
+            GroupGiftOpened {
+                gift_id,
+                organizer,
+                unlock_timestamp,
+                funding_deadline,
+                recipient_hash: recipient_phone_hash,
+            },
+        );
+
+        Ok(gift_id)
+    }
+
+    fn contribute(env: Env, gift_id: u64, contributor: Address, amount: i128) -> Result<(), Error> {
+        contributor.require_auth();
+
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        let mut group = storage::get_group_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        group::ensure_collecting(&env, &gift, &group)?;
+
+        if amount <= 0 || gift.amount + amount > constants::MAX_GIFT_AMOUNT {
+            return Err(Error::InvalidAmount);
+        }
+
+        group::record_contribution(&env, gift_id, &mut group, &contributor, amount)?;
+        storage::set_group_gift(&env, gift_id, &group);
+
+        gift.amount += amount;
+        storage::set_gift(&env, gift_id, &gift);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &contributor,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + amount;
+        storage::set_total_held(&env, total_held);
+
+        env.events().publish(
+            (symbol_short!("grp_cntrb"),),
+            ContributionReceived {
+                gift_id,
+                contributor,
+                amount,
+                total_amount: gift.amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn close_group_gift(env: Env, gift_id: u64) -> Result<(), Error> {
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        let group = storage::get_group_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        if gift.status != GiftStatus::Collecting {
+            return Err(Error::InvalidStatus);
+        }
+
+        // Before the deadline only the organizer can stop collection early
+        if env.ledger().timestamp() <= group.funding_deadline {
+            group.organizer.require_auth();
+        }
+
+        if gift.amount < constants::MIN_GIFT_AMOUNT {
+            return Err(Error::InvalidAmount);
+        }
+
+        gift.status = GiftStatus::Created;
+        storage::set_gift(&env, gift_id, &gift);
+
+        let total_gifted = storage::get_total_gifted(&env) + gift.amount;
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("grp_close"),),
+            GroupGiftClosed {
+                gift_id,
+                amount: gift.amount,
+                contributors: group.contributors.len(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn cancel_group_gift(env: Env, gift_id: u64) -> Result<i128, Error> {
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        let group = storage::get_group_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        if gift.status != GiftStatus::Collecting {
+            return Err(Error::InvalidStatus);
+        }
+
+        // Before the deadline only the organizer can call off the collection
+        if env.ledger().timestamp() <= group.funding_deadline {
+            group.organizer.require_auth();
+        }
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        let refund_amount = group::refund_contributors(&env, &usdc_address, gift_id, &group)?;
+
+        gift.status = GiftStatus::Refunded;
+        storage::set_gift(&env, gift_id, &gift);
+
+        let total_held = storage::get_total_held(&env) - refund_amount;
+        storage::set_total_held(&env, total_held);
+
+        env.events().publish(
+            (symbol_short!("grp_cncl"),),
+            GroupGiftCancelled {
+                gift_id,
+                refund_amount,
+                contributors: group.contributors.len(),
+            },
+        );
+
+        Ok(refund_amount)
+    }
+
+    fn get_group_gift(env: Env, gift_id: u64) -> Result<GroupGift, Error> {
+        storage::get_group_gift(&env, gift_id).ok_or(Error::GiftNotFound)
+    }
+
+    fn get_contribution(env: Env, gift_id: u64, contributor: Address) -> Result<i128, Error> {
+        Ok(storage::get_contribution(&env, gift_id, &contributor))
+    }
+
+    fn create_split_gift(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        unlock_timestamp: u64,
+        claim_deadline: u64,
+        shares: Vec<SplitShare>,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        if amount > constants::MAX_GIFT_AMOUNT {
+            return Err(Error::InvalidAmount);
+        }
+        let current_time = env.ledger().timestamp();
+        if unlock_timestamp <= current_time || claim_deadline <= unlock_timestamp {
+            return Err(Error::InvalidUnlockTime);
+        }
+        if claim_deadline - current_time > constants::MAX_LOCK_DURATION {
+            return Err(Error::UnlockTimestampTooFar);
+        }
+        let amounts = split::share_amounts(&env, amount, &shares)?;
+
+        let split_id = storage::increment_next_split_id(&env);
+        let gift_ids =
+            split::issue_share_gifts(&env, split_id, &sender, unlock_timestamp, &shares, &amounts);
+        let split = SplitGift {
+            sender: sender.clone(),
+            amount,
+            claim_deadline,
+            gift_ids: gift_ids.clone(),
+        };
+        storage::set_split_gift(&env, split_id, &split);
+
+        // One escrow for every share
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &sender,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + amount;
+        let total_gifted = storage::get_total_gifted(&env) + amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("split_new"),),
+            SplitGiftCreated {
+                split_id,
+                sender,
+                amount,
+                gift_ids,
+                unlock_timestamp,
+                claim_deadline,
+            },
+        );
+
+        Ok(split_id)
+    }
+
+    fn refund_unclaimed_shares(env: Env, split_id: u64) -> Result<i128, Error> {
+        let split = storage::get_split_gift(&env, split_id).ok_or(Error::GiftNotFound)?;
+        split.sender.require_auth();
+
+        if env.ledger().timestamp() <= split.claim_deadline {
+            return Err(Error::ClaimPeriodNotOver);
+        }
+
+        let mut refunded_shares = 0;
+        let mut refund_amount = 0;
+        for gift_id in split.gift_ids.iter() {
+            let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+            if gift.status != GiftStatus::Created {
+                continue;
+            }
+            refunded_shares += 1;
+            refund_amount += gift.amount;
+            gift.status = GiftStatus::Refunded;
+            storage::set_gift(&env, gift_id, &gift);
+        }
+        if refunded_shares == 0 {
+            return Ok(0);
+        }
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer(&env, &usdc_address, &split.sender, refund_amount)?;
+
+        let total_held = storage::get_total_held(&env) - refund_amount;
+        let total_gifted = storage::get_total_gifted(&env) - refund_amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("split_rfd"),),
+            SplitSharesRefunded {
+                split_id,
+                sender: split.sender,
+                refunded_shares,
+                refund_amount,
+            },
+        );
+
+        Ok(refund_amount)
+    }
+
+    fn get_split_gift(env: Env, split_id: u64) -> Result<SplitGift, Error> {
+        storage::get_split_gift(&env, split_id).ok_or(Error::GiftNotFound)
+    }
+
+    fn is_split_gift_settled(env: Env, split_id: u64) -> Result<bool, Error> {
+        let split = storage::get_split_gift(&env, split_id).ok_or(Error::GiftNotFound)?;
+        Ok(split::is_settled(&env, &split))
+    }
+
+    fn create_red_envelope(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        max_claimers: u32,
+        min_share: i128,
+        allowlist: Option<Vec<BytesN<32>>>,
+        expires_at: u64,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        red_envelope::validate_red_envelope(
+            &env,
+            amount,
+            max_claimers,
+            min_share,
+            &allowlist,
+            expires_at,
+        )?;
+
+        let envelope_id = storage::increment_next_envelope_id(&env);
+        let restricted = allowlist.is_some();
+        let envelope = RedEnvelope {
+            sender: sender.clone(),
+            amount,
+            remaining: amount,
+            max_claimers,
+            min_share,
+            allowlist,
+            claimers: Vec::new(&env),
+            claimed_hashes: Vec::new(&env),
+            gift_ids: Vec::new(&env),
+            expires_at,
+        };
+        storage::set_red_envelope(&env, envelope_id, &envelope);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &sender,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + amount;
+        let total_gifted = storage::get_total_gifted(&env) + amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("env_new"),),
+            RedEnvelopeCreated {
+                envelope_id,
+                sender,
+                amount,
+                max_claimers,
+                min_share,
+                restricted,
+                expires_at,
+            },
+        );
+
+        Ok(envelope_id)
+    }
+
+    fn claim_red_envelope(
+        env: Env,
+        envelope_id: u64,
+        claimant: Address,
+        recipient_phone_hash: Option<BytesN<32>>,
+        verification_proof: Option<BytesN<64>>,
+    ) -> Result<u64, Error> {
+        claimant.require_auth();
+
+        let mut envelope =
+            storage::get_red_envelope(&env, envelope_id).ok_or(Error::GiftNotFound)?;
+        if red_envelope::is_exhausted(&envelope) {
+            return Err(Error::RedEnvelopeExhausted);
+        }
+        if env.ledger().timestamp() > envelope.expires_at {
+            return Err(Error::DeadlineExceeded);
+        }
+        if envelope.claimers.contains(&claimant) {
+            return Err(Error::AlreadyClaimed);
+        }
+
+        // Restricted envelopes need an oracle proof for an allowlisted, unused phone hash
+        let phone_hash = match (&envelope.allowlist, recipient_phone_hash) {
+            (Some(allowlist), Some(phone_hash)) => {
+                if !allowlist.contains(&phone_hash) {
+                    return Err(Error::Unauthorized);
+                }
+                if envelope.claimed_hashes.contains(&phone_hash) {
+                    return Err(Error::AlreadyClaimed);
+                }
+                let proof = verification_proof.ok_or(Error::InvalidProof)?;
+                Self::verify_claim_proof(&env, &claimant, None, &phone_hash, &proof);
+                envelope.claimed_hashes.push_back(phone_hash.clone());
+                phone_hash
+            }
+            (Some(_), None) => return Err(Error::InvalidProof),
+            (None, _) => BytesN::from_array(&env, &[0u8; 32]),
+        };
+
+        let share = red_envelope::draw_share(&env, &envelope);
+        envelope.remaining -= share;
+        envelope.claimers.push_back(claimant.clone());
+
+        // The share is issued as a gift the claimant has already claimed
+        let gift_id = storage::increment_next_gift_id(&env);
+        let gift = Gift {
+            sender: envelope.sender.clone(),
+            recipient: Some(claimant.clone()),
+            amount: share,
+            withdrawn_amount: 0,
+            unlock_timestamp: env.ledger().timestamp(),
+            recipient_phone_hash: phone_hash,
+            status: GiftStatus::Unlocked,
+            kind: GiftKind::Standard,
+        };
+        storage::set_gift(&env, gift_id, &gift);
+        envelope.gift_ids.push_back(gift_id);
+        storage::set_red_envelope(&env, envelope_id, &envelope);
+
+        env.events().publish(
+            (Symbol::new(&env, "gift_created"),),
+            GiftCreated {
+                gift_id,
+                sender: gift.sender,
+                amount: share,
+                unlock_time: gift.unlock_timestamp,
+                recipient_hash: gift.recipient_phone_hash,
+            },
+        );
+
+        env.events().publish(
+            (symbol_short!("env_claim"),),
+            RedEnvelopeClaimed {
+                envelope_id,
+                claimant,
+                gift_id,
+                share,
+                claim_number: envelope.claimers.len(),
+                remaining: envelope.remaining,
+            },
+        );
+
+        if red_envelope::is_exhausted(&envelope) {
+            env.events().publish(
+                (symbol_short!("env_empty"),),
+                RedEnvelopeExhausted {
+                    envelope_id,
+                    claimers: envelope.claimers.len(),
+                },
+            );
+        }
+
+        Ok(gift_id)
+    }
+
+    fn refund_red_envelope(env: Env, envelope_id: u64) -> Result<i128, Error> {
+        let mut envelope =
+            storage::get_red_envelope(&env, envelope_id).ok_or(Error::GiftNotFound)?;
+        envelope.sender.require_auth();
+
+        if env.ledger().timestamp() <= envelope.expires_at {
+            return Err(Error::ClaimPeriodNotOver);
+        }
+        if envelope.remaining == 0 {
+            return Err(Error::RedEnvelopeExhausted);
+        }
+
+        let refund_amount = envelope.remaining;
+        envelope.remaining = 0;
+        storage::set_red_envelope(&env, envelope_id, &envelope);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer(&env, &usdc_address, &envelope.sender, refund_amount)?;
+
+        let total_held = storage::get_total_held(&env) - refund_amount;
+        let total_gifted = storage::get_total_gifted(&env) - refund_amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("env_rfnd"),),
+            RedEnvelopeRefunded {
+                envelope_id,
+                sender: envelope.sender,
+                refund_amount,
+            },
+        );
+
+        Ok(refund_amount)
+    }
+
+    fn get_red_envelope(env: Env, envelope_id: u64) -> Result<RedEnvelope, Error> {
+        storage::get_red_envelope(&env, envelope_id).ok_or(Error::GiftNotFound)
+    }
+
+    fn create_gift_schedule(
+        env: Env,
+        sender: Address,
+        recipient_phone_hash: BytesN<32>,
+        amount: i128,
+        interval: u64,
+        count: u32,
+        first_unlock: u64,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        schedule::validate_schedule(&env, amount, interval, count, first_unlock)?;
+
+        let schedule_id = storage::increment_next_schedule_id(&env);
+        let schedule = GiftSchedule {
+            sender: sender.clone(),
+            recipient_phone_hash: recipient_phone_hash.clone(),
+            amount,
+            interval,
+            count,
+            first_unlock,
+            gift_ids: Vec::new(&env),
+            status: ScheduleStatus::Active,
+        };
+        storage::set_schedule(&env, schedule_id, &schedule);
+
+        // Escrow every installment up front
+        let total_amount = schedule::total_amount(&schedule)?;
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &sender,
+            &env.current_contract_address(),
+            total_amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + total_amount;
+        storage::set_total_held(&env, total_held);
+
+        env.events().publish(
+            (symbol_short!("sched_new"),),
+            GiftScheduleCreated {
+                schedule_id,
+                sender,
+                amount,
+                interval,
+                count,
+                first_unlock,
+                recipient_hash: recipient_phone_hash,
+            },
+        );
+
+        Ok(schedule_id)
+    }
+
+    fn issue_schedule_installments(env: Env, schedule_id: u64) -> Result<Vec<u64>, Error> {
+        let mut schedule =
+            storage::get_schedule(&env, schedule_id).ok_or(Error::GiftNotFound)?;
+
+        let issued = schedule::issue_due_installments(&env, schedule_id, &mut schedule)?;
+        if !issued.is_empty() {
+            storage::set_schedule(&env, schedule_id, &schedule);
+        }
+
+        Ok(issued)
+    }
+
+    fn cancel_gift_schedule(env: Env, schedule_id: u64) -> Result<i128, Error> {
+        let mut schedule =
+            storage::get_schedule(&env, schedule_id).ok_or(Error::GiftNotFound)?;
+        schedule.sender.require_auth();
+
+        let refund_amount = schedule::cancel(&env, schedule_id, &mut schedule)?;
+        storage::set_schedule(&env, schedule_id, &schedule);
+
+        Ok(refund_amount)
+    }
+
+    fn get_gift_schedule(env: Env, schedule_id: u64) -> Result<GiftSchedule, Error> {
+        storage::get_schedule(&env, schedule_id).ok_or(Error::GiftNotFound)
+    }
+
+    fn get_schedule_installments(
+        env: Env,
+        schedule_id: u64,
+    ) -> Result<Vec<ScheduleInstallment>, Error> {
+        let schedule = storage::get_schedule(&env, schedule_id).ok_or(Error::GiftNotFound)?;
+        schedule::installments(&env, &schedule)
+    }
+
+    fn top_up_gift(env: Env, sender: Address, gift_id: u64, amount: i128) -> Result<(), Error> {
+        sender.require_auth();
+
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        if gift.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if gift.status != GiftStatus::Created {
+            return Err(Error::InvalidStatus);
+        }
+        // Vesting and milestone gifts pay out per their terms, which a top-up would not cover
+        if gift.kind != GiftKind::Standard {
+            return Err(Error::InvalidGiftKind);
+        }
+        if amount <= 0 || gift.amount + amount > constants::MAX_GIFT_AMOUNT {
+            return Err(Error::InvalidAmount);
+        }
+
+        gift.amount += amount;
+        storage::set_gift(&env, gift_id, &gift);
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &sender,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let total_held = storage::get_total_held(&env) + amount;
+        let total_gifted = storage::get_total_gifted(&env) + amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("topped_up"),),
+            GiftToppedUp {
+                gift_id,
+                sender,
+                amount,
+                new_amount: gift.amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn update_recipient(
+        env: Env,
+        sender: Address,
+        gift_id: u64,
+        new_phone_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+
+        // Deposit-created gifts have no sender wallet; the oracle attests the change
+        let authorized = if gift.sender == env.current_contract_address() {
+            let oracle_config =
+                storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
+            oracle_config.oracle_address
+        } else {
+            gift.sender.clone()
+        };
+        if sender != authorized {
+            return Err(Error::Unauthorized);
+        }
+
+        if gift.status != GiftStatus::Created {
+            return Err(Error::InvalidStatus);
+        }
+
+        let change_count = storage::get_recipient_changes(&env, gift_id) + 1;
+        if change_count > constants::MAX_RECIPIENT_CHANGES {
+            return Err(Error::LimitReached);
+        }
+        storage::set_recipient_changes(&env, gift_id, change_count);
+
+        let old_phone_hash = gift.recipient_phone_hash;
+        gift.recipient_phone_hash = new_phone_hash.clone();
+        storage::set_gift(&env, gift_id, &gift);
+
+        env.events().publish(
+            (symbol_short!("rcpt_upd"),),
+            RecipientUpdated {
+                gift_id,
+                updated_by: sender,
+                old_phone_hash,
+                new_phone_hash,
+                change_count,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_recipient_change_count(env: Env, gift_id: u64) -> Result<u32, Error> {
+        Ok(storage::get_recipient_changes(&env, gift_id))
+    }
+
+    fn reject_gift(env: Env, gift_id: u64, attestation: BytesN<64>) -> Result<(), Error> {
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        if gift.status != GiftStatus::Created {
+            return Err(Error::InvalidStatus);
+        }
+
+        Self::verify_rejection_proof(&env, gift_id, &gift.recipient_phone_hash, &attestation);
+
+        let remaining = withdrawal::remaining_amount(&gift);
+        gift.status = GiftStatus::Rejected;
+        storage::set_gift(&env, gift_id, &gift);
+
+        // Group gifts go back to their contributors rather than the organizer,
+        // who only gets back what they topped up after closing.
+        // Deposit-created gifts were paid for off-chain, so the oracle that
+        // attested the deposit receives the refund and settles it with the payer.
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        let mut refund_amount = remaining;
+        let refunded_to = if let Some(group) = storage::get_group_gift(&env, gift_id) {
+            refund_amount = group::refund_contributors(&env, &usdc_address, gift_id, &group)?;
+            let top_ups = remaining - refund_amount;
+            if top_ups > 0 {
+                token::transfer(&env, &usdc_address, &gift.sender, top_ups)?;
+                refund_amount += top_ups;
+            }
+            None
+        } else if gift.sender == env.current_contract_address() {
+            let oracle_config =
+                storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
+            token::transfer(&env, &usdc_address, &oracle_config.oracle_address, refund_amount)?;
+            Some(oracle_config.oracle_address)
+        } else {
+            token::transfer(&env, &usdc_address, &gift.sender, refund_amount)?;
+            Some(gift.sender.clone())
+        };
+
+        let total_held = storage::get_total_held(&env) - refund_amount;
+        let total_gifted = storage::get_total_gifted(&env) - refund_amount;
+        storage::set_total_held(&env, total_held);
+        storage::set_total_gifted(&env, total_gifted);
+
+        env.events().publish(
+            (symbol_short!("rejected"),),
+            GiftRejected {
+                gift_id,
+                sender: gift.sender,
+                refunded_to,
+                refund_amount,
+                recipient_phone_hash: gift.recipient_phone_hash,
+            },
+        );
+
+        Ok(())
+    }
+
     fn claim_gift(
         env: Env,
         claimant: Address,
@@ -116,184 +1048,351 @@ impl TimeLockTrait for TimeLockContract {
         verification_proof: BytesN<64>,
     ) -> Result<(), Error> {
         claimant.require_auth();
+        Self::claim(&env, &claimant, None, gift_id, &verification_proof)
+    }
+
+    fn claim_gift_for(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        payout_address: Address,
+        verification_proof: BytesN<64>,
+    ) -> Result<(), Error> {
+        claimant.require_auth();
+        Self::claim(&env, &claimant, Some(&payout_address), gift_id, &verification_proof)
+    }
 
-        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
-
-        if gift.status != GiftStatus::Created {
-            return Err(if gift.status == GiftStatus::Claimed {
-                Error::AlreadyClaimed
-            } else {
-                Error::InvalidStatus
-            });
+    fn relay_claim(
+        env: Env,
+        relayer: Address,
+        recipient: Address,
+        gift_id: u64,
+        verification_proof: BytesN<64>,
+        relay_fee: i128,
+    ) -> Result<(), Error> {
+        relayer.require_auth();
+        if !storage::is_relayer_allowed(&env, &relayer) {
+            return Err(Error::RelayerNotAllowed);
+        }
+        if relay_fee < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if relay_fee > storage::get_max_relay_fee(&env) {
+            return Err(Error::RelayFeeTooHigh);
         }
 
-        if env.ledger().timestamp() < gift.unlock_timestamp {
-            return Err(Error::NotUnlocked);
+        recipient.require_auth();
+        Self::claim(&env, &recipient, None, gift_id, &verification_proof)?;
+
+        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        if relay_fee > 0 {
+            // The relayer is reimbursed out of the gift, leaving the rest withdrawable
+            if relay_fee >= withdrawal::available_amount(&env, &gift)? {
+                return Err(Error::RelayFeeTooHigh);
+            }
+            let usdc_address = withdrawal::ensure_contract_balance(&env, relay_fee)?;
+            token::transfer(&env, &usdc_address, &relayer, relay_fee)?;
+
+            gift.withdrawn_amount += relay_fee;
+            storage::set_gift(&env, gift_id, &gift);
+
+            let total_held = storage::get_total_held(&env) - relay_fee;
+            storage::set_total_held(&env, total_held);
         }
 
-        let oracle_pk = storage::get_oracle_auth_key(&env);
+        env.events().publish(
+            (symbol_short!("relay_clm"),),
+            ClaimRelayed {
+                gift_id,
+                relayer,
+                recipient,
+                relay_fee,
+                remaining_amount: withdrawal::remaining_amount(&gift),
+            },
+        );
 
-        let mut payload = Bytes::new(&env);
-        payload.append(&claimant.clone().to_xdr(&env));
-        payload.append(&gift.recipient_phone_hash.clone().to_xdr(&env));
+        Ok(())
+    }
 
-        env.crypto()
-            .ed25519_verify(&oracle_pk, &payload, &verification_proof);
+    fn set_relayer_allowed(env: Env, relayer: Address, allowed: bool) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
 
-        gift.recipient = Some(claimant.clone());
-        gift.status = GiftStatus::Unlocked;
+        storage::set_relayer_allowed(&env, &relayer, allowed);
 
-        storage::set_gift(&env, gift_id, &gift);
+        env.events().publish(
+            (symbol_short!("relay_up"),),
+            RelayerUpdated { relayer, allowed },
+        );
+
+        Ok(())
+    }
+
+    fn is_relayer_allowed(env: Env, relayer: Address) -> Result<bool, Error> {
+        Ok(storage::is_relayer_allowed(&env, &relayer))
+    }
+
+    fn set_max_relay_fee(env: Env, max_relay_fee: i128) -> Result<(), Error> {
+        if max_relay_fee < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let old_max_relay_fee = storage::get_max_relay_fee(&env);
+        storage::set_max_relay_fee(&env, max_relay_fee);
 
         env.events().publish(
-            (symbol_short!("claimed"),),
-            (gift_id, claimant, env.ledger().timestamp()),
+            (symbol_short!("relay_fee"),),
+            RelayFeeCapUpdated {
+                old_max_relay_fee,
+                new_max_relay_fee: max_relay_fee,
+            },
         );
 
         Ok(())
     }
 
+    fn get_max_relay_fee(env: Env) -> Result<i128, Error> {
+        Ok(storage::get_max_relay_fee(&env))
+    }
+
+    fn claim_and_withdraw(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        verification_proof: BytesN<64>,
+    ) -> Result<(), Error> {
+        claimant.require_auth();
+        Self::claim(&env, &claimant, None, gift_id, &verification_proof)?;
+        Self::pay_to_wallet(&env, gift_id, None)
+    }
+
+    fn claim_and_withdraw_to_bank(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        verification_proof: BytesN<64>,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error> {
+        claimant.require_auth();
+        Self::claim(&env, &claimant, None, gift_id, &verification_proof)?;
+        Self::start_bank_withdrawal(&env, gift_id, None, payout, quote)
+    }
+
     fn withdraw_to_bank(
         env: Env,
         gift_id: u64,
-        recipient_bank_details_hash: String,
-        anchor_address: Address,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error> {
+        Self::require_recipient_auth(&env, gift_id)?;
+        Self::start_bank_withdrawal(&env, gift_id, None, payout, quote)
+    }
+
+    fn withdraw_to_bank_partial(
+        env: Env,
+        gift_id: u64,
+        amount: i128,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error> {
+        Self::require_recipient_auth(&env, gift_id)?;
+        Self::start_bank_withdrawal(&env, gift_id, Some(amount), payout, quote)
+    }
+
+    fn confirm_settlement(
+        env: Env,
+        gift_id: u64,
+        anchor_tx_id: String,
+        anchor_signature: BytesN<64>,
     ) -> Result<(), Error> {
         let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
 
-        if gift.status != GiftStatus::Unlocked {
+        if gift.status != GiftStatus::PendingSettlement {
             return Err(Error::InvalidStatus);
         }
 
-        let recipient = gift.recipient.as_ref().ok_or(Error::Unauthorized)?;
-        recipient.require_auth();
+        let pending = storage::get_settlement(&env, gift_id).ok_or(Error::SettlementNotFound)?;
+        if pending.held {
+            return Err(Error::InvalidStatus);
+        }
+        let anchor_config =
+            storage::get_anchor(&env, &pending.anchor_address).ok_or(Error::AnchorNotRegistered)?;
+        anchor::verify_confirmation(
+            &env,
+            &anchor_config,
+            gift_id,
+            &pending,
+            &anchor_tx_id,
+            &anchor_signature,
+        )?;
+
+        settlement::pay_anchor(&env, &pending)?;
+        insurance::settle_top_up(&env, gift_id, &pending);
 
-        let fee_amount = (gift.amount * constants::GIFT_FEE_BPS as i128) / 10000;
-        let amount_after_fee = gift.amount - fee_amount;
+        let withdrawn = pending.amount_usdc + pending.fee_amount;
+        gift.withdrawn_amount += withdrawn;
+        let remaining_amount = withdrawal::remaining_amount(&gift);
 
         env.events().publish(
             (symbol_short!("fee_coll"),),
             FeesCollected {
                 gift_id,
-                fee_amount_usdc: fee_amount,
+                fee_amount_usdc: pending.fee_amount,
             },
         );
 
-        let oracle_config = storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
-        let oracle_rate = oracle::get_price(&env, &oracle_config)?;
-        let expected_ngn = (amount_after_fee * oracle_rate) / 1_000_000;
-
         env.events().publish(
-            (symbol_short!("bank_init"),),
-            BankWithdrawalInitiated {
-                gift_id,
-                amount_usdc: amount_after_fee,
-                expected_ngn,
+            (symbol_short!("anc_dep"),),
+            AnchorDepositSent {
+                anchor_address: pending.anchor_address.clone(),
+                asset: pending.payout_asset.clone(),
+                amount: pending.payout_amount,
+                memo: pending.memo.clone(),
             },
         );
 
-        let slippage_config = storage::get_slippage_config(&env).ok_or(Error::Unauthorized)?;
-        let min_ngn = (expected_ngn * (10000 - slippage_config.max_slippage_bps as i128)) / 10000;
-
-        let path = path_payment::discover_optimal_path(
-            &env,
-            &recipient.clone(),
-            &anchor_address,
-            amount_after_fee,
-        )?;
-
-        let swap_result = path_payment::execute_path_payment(
-            &env,
-            amount_after_fee,
-            min_ngn,
-            path.clone(),
-            &anchor_address,
-        )?;
-
         env.events().publish(
-            (symbol_short!("path_exec"),),
-            PathPaymentExecuted {
-                usdc_sent: swap_result.amount_in,
-                ngn_received: swap_result.amount_out,
-                exchange_rate: (swap_result.amount_out * 1_000_000) / swap_result.amount_in,
-                path,
+            (symbol_short!("settle_ok"),),
+            SettlementConfirmed {
+                gift_id,
+                anchor_address: pending.anchor_address.clone(),
+                anchor_tx_id,
+                amount_usdc: pending.amount_usdc,
+                remaining_amount,
             },
         );
 
-        env.events().publish(
-            (symbol_short!("anc_dep"),),
-            AnchorDepositSent {
-                anchor_address,
-                ngn_amount: swap_result.amount_out,
-                memo: recipient_bank_details_hash,
-            },
-        );
+        let escrow = storage::get_anchor_escrow(&env, &pending.anchor_address) - pending.amount_usdc;
+        storage::set_anchor_escrow(&env, &pending.anchor_address, escrow);
+        storage::remove_settlement(&env, gift_id);
 
-        gift.status = GiftStatus::Withdrawn;
+        gift.status = if remaining_amount == 0 {
+            GiftStatus::Withdrawn
+        } else {
+            GiftStatus::Unlocked
+        };
         storage::set_gift(&env, gift_id, &gift);
 
         // Internal Tracking: Collect Platform Fee
-        let total_fees = storage::get_total_fees(&env) + fee_amount;
-        storage::set_total_fees(&env, total_fees);
-
-        // Note: total_held doesn't change yet as the contract still holds the USDC
-        // until it's actually swapped/sent.
-        // In this implementation, withdraw_to_bank simulates the swap.
-        // For strict tracking, we decrease total_held by the full gift amount.
-        let total_held = storage::get_total_held(&env) - gift.amount;
+        insurance::collect_fee(&env, gift_id, pending.fee_amount);
+
+        // The post-fee USDC has left the contract and the fee moved to total_fees,
+        // so the withdrawn part of the gift no longer counts towards total_held
+        let total_held = storage::get_total_held(&env) - withdrawn;
         storage::set_total_held(&env, total_held);
 
         Ok(())
     }
 
-    fn withdraw_gift(env: Env, gift_id: u64) -> Result<(), Error> {
-        let mut gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+    fn fail_settlement(env: Env, gift_id: u64, reason: String) -> Result<(), Error> {
+        let gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
 
-        if gift.status != GiftStatus::Unlocked {
+        if gift.status != GiftStatus::PendingSettlement {
             return Err(Error::InvalidStatus);
         }
 
-        let recipient = gift.recipient.as_ref().ok_or(Error::Unauthorized)?;
-        recipient.require_auth();
-
-        let fee_amount = (gift.amount * constants::GIFT_FEE_BPS as i128) / 10000;
-        let amount_after_fee = gift.amount - fee_amount;
-
-        // Internal Tracking: Collect Platform Fee
-        let total_fees = storage::get_total_fees(&env) + fee_amount;
-        storage::set_total_fees(&env, total_fees);
+        let pending = storage::get_settlement(&env, gift_id).ok_or(Error::SettlementNotFound)?;
+        if pending.held {
+            return Err(Error::InvalidStatus);
+        }
+        pending.anchor_address.require_auth();
 
-        // Update internal accounting: Decrease total_held by the full gift amount
-        let total_held = storage::get_total_held(&env) - gift.amount;
-        storage::set_total_held(&env, total_held);
+        settlement::release_or_hold(&env, gift_id, &pending)?;
 
-        // Events
         env.events().publish(
-            (symbol_short!("fee_coll"),),
-            FeesCollected {
+            (symbol_short!("settle_f"),),
+            SettlementFailed {
                 gift_id,
-                fee_amount_usdc: fee_amount,
+                anchor_address: pending.anchor_address,
+                reason,
             },
         );
 
+        Ok(())
+    }
+
+    fn expire_settlement(env: Env, gift_id: u64) -> Result<(), Error> {
+        let gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+
+        if gift.status != GiftStatus::PendingSettlement {
+            return Err(Error::InvalidStatus);
+        }
+
+        let pending = storage::get_settlement(&env, gift_id).ok_or(Error::SettlementNotFound)?;
+        if pending.held {
+            return Err(Error::InvalidStatus);
+        }
+        if env.ledger().timestamp() < pending.initiated_at + constants::SETTLEMENT_TIMEOUT {
+            return Err(Error::SettlementNotExpired);
+        }
+
+        settlement::release_or_hold(&env, gift_id, &pending)?;
+
         env.events().publish(
-            (symbol_short!("withdr_s"),),
-            WithdrawalSuccess {
+            (symbol_short!("settle_x"),),
+            SettlementExpired {
                 gift_id,
-                recipient: recipient.clone(),
-                amount_withdrawn: amount_after_fee,
-                timestamp: env.ledger().timestamp(),
+                anchor_address: pending.anchor_address,
+                amount_usdc: pending.amount_usdc,
             },
         );
 
-        // Transfer USDC to recipient
-        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
-        token::transfer(&env, &usdc_address, recipient, amount_after_fee)?;
+        Ok(())
+    }
 
-        gift.status = GiftStatus::Withdrawn;
-        storage::set_gift(&env, gift_id, &gift);
+    fn retry_settlement_release(env: Env, gift_id: u64) -> Result<(), Error> {
+        let pending = storage::get_settlement(&env, gift_id).ok_or(Error::SettlementNotFound)?;
+        if !pending.held {
+            return Err(Error::InvalidStatus);
+        }
 
-        Ok(())
+        settlement::release(&env, gift_id, &pending)
+    }
+
+    fn accept_settlement_payout(env: Env, gift_id: u64) -> Result<(), Error> {
+        let gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        let pending = storage::get_settlement(&env, gift_id).ok_or(Error::SettlementNotFound)?;
+        if !pending.held {
+            return Err(Error::InvalidStatus);
+        }
+
+        let recipient = gift.recipient.ok_or(Error::Unauthorized)?;
+        recipient.require_auth();
+
+        settlement::pay_recipient(&env, gift_id, &pending)
+    }
+
+    fn get_settlement(env: Env, gift_id: u64) -> Result<Settlement, Error> {
+        storage::get_settlement(&env, gift_id).ok_or(Error::SettlementNotFound)
+    }
+
+    fn get_anchor_escrow(env: Env, anchor: Address) -> Result<i128, Error> {
+        Ok(storage::get_anchor_escrow(&env, &anchor))
+    }
+
+    fn withdraw_gift(env: Env, gift_id: u64) -> Result<(), Error> {
+        Self::require_recipient_auth(&env, gift_id)?;
+        Self::pay_to_wallet(&env, gift_id, None)
+    }
+
+    fn withdraw_gift_partial(env: Env, gift_id: u64, amount: i128) -> Result<(), Error> {
+        Self::require_recipient_auth(&env, gift_id)?;
+        Self::pay_to_wallet(&env, gift_id, Some(amount))
+    }
+
+    fn preview_withdrawal(
+        env: Env,
+        gift_id: u64,
+        method: WithdrawalMethod,
+        currency: String,
+        anchor: Option<Address>,
+    ) -> Result<WithdrawalPreview, Error> {
+        let gift = storage::get_gift(&env, gift_id).ok_or(Error::GiftNotFound)?;
+        Ok(withdrawal::preview(&env, &gift, method, currency, anchor))
     }
 
     fn withdraw_accumulated_fees(env: Env, to: Address) -> Result<(), Error> {
@@ -308,46 +1407,213 @@ impl TimeLockTrait for TimeLockContract {
         let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
         token::transfer(&env, &usdc_address, &to, total_fees)?;
 
-        storage::set_total_fees(&env, 0);
+        storage::set_total_fees(&env, 0);
+
+        env.events().publish(
+            (symbol_short!("fee_wdr"),),
+            FeeWithdrawal { total_fees, to },
+        );
+
+        Ok(())
+    }
+
+    fn set_anchor(env: Env, anchor: Address, config: AnchorConfig) -> Result<(), Error> {
+        anchor::validate_anchor_config(&config)?;
+
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let mut anchors = storage::get_anchor_list(&env);
+        if !anchors.contains(&anchor) {
+            anchors.push_back(anchor.clone());
+            storage::set_anchor_list(&env, &anchors);
+        }
+        storage::set_anchor(&env, &anchor, &config);
+
+        env.events().publish(
+            (symbol_short!("anchor_up"),),
+            AnchorUpdated {
+                anchor_address: anchor,
+                config,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn set_anchor_enabled(env: Env, anchor: Address, enabled: bool) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let mut config = storage::get_anchor(&env, &anchor).ok_or(Error::AnchorNotRegistered)?;
+        config.enabled = enabled;
+        storage::set_anchor(&env, &anchor, &config);
+
+        env.events().publish(
+            (symbol_short!("anchor_up"),),
+            AnchorUpdated {
+                anchor_address: anchor,
+                config,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_anchor(env: Env, anchor: Address) -> Result<AnchorConfig, Error> {
+        storage::get_anchor(&env, &anchor).ok_or(Error::AnchorNotRegistered)
+    }
+
+    fn get_anchors_for_currency(env: Env, currency: String) -> Result<Vec<Address>, Error> {
+        let mut anchors = Vec::new(&env);
+        for anchor in storage::get_anchor_list(&env).iter() {
+            if let Some(config) = storage::get_anchor(&env, &anchor) {
+                if config.enabled && config.currencies.contains(&currency) {
+                    anchors.push_back(anchor);
+                }
+            }
+        }
+        Ok(anchors)
+    }
+
+    fn set_oracle_address(env: Env, new_oracle_address: Address) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let mut oracle_config = storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
+        let old_address = oracle_config.oracle_address.clone();
+        oracle_config.oracle_address = new_oracle_address.clone();
+
+        storage::set_oracle_config(&env, &oracle_config);
+
+        env.events().publish(
+            (symbol_short!("oracle_ad"),),
+            OracleAddressUpdated {
+                old_address,
+                new_address: new_oracle_address,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn set_dex_router(env: Env, router: Address) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let old_router = storage::get_dex_router(&env);
+        storage::set_dex_router(&env, &router);
+
+        env.events().publish(
+            (symbol_short!("router_up"),),
+            DexRouterUpdated {
+                old_router,
+                new_router: router,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn set_payout_token(env: Env, currency: String, token: Address) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        storage::set_payout_token(&env, &currency, &token);
+
+        Ok(())
+    }
+
+    fn add_intermediate_asset(env: Env, asset: Address) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let mut assets = storage::get_intermediate_assets(&env);
+        if assets.contains(&asset) {
+            return Ok(());
+        }
+        if assets.len() >= constants::MAX_INTERMEDIATE_ASSETS {
+            return Err(Error::TooManyIntermediateAssets);
+        }
+
+        assets.push_back(asset);
+        storage::set_intermediate_assets(&env, &assets);
+
+        Ok(())
+    }
 
-        env.events().publish(
-            (symbol_short!("fee_wdr"),),
-            FeeWithdrawal { total_fees, to },
-        );
+    fn remove_intermediate_asset(env: Env, asset: Address) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let mut assets = storage::get_intermediate_assets(&env);
+        if let Some(index) = assets.first_index_of(&asset) {
+            assets.remove(index);
+            storage::set_intermediate_assets(&env, &assets);
+        }
 
         Ok(())
     }
 
-    fn set_oracle_address(env: Env, new_oracle_address: Address) -> Result<(), Error> {
+    fn get_intermediate_assets(env: Env) -> Result<Vec<Address>, Error> {
+        Ok(storage::get_intermediate_assets(&env))
+    }
+
+    fn set_max_oracle_age(env: Env, max_age: u64) -> Result<(), Error> {
         let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
         admin.require_auth();
 
         let mut oracle_config = storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
-        let old_address = oracle_config.oracle_address.clone();
-        oracle_config.oracle_address = new_oracle_address.clone();
+        oracle::validate_staleness_policy(
+            oracle_config.staleness_mode,
+            max_age,
+            oracle_config.max_oracle_age_ledgers,
+        )?;
+        oracle_config.max_oracle_age = max_age;
 
         storage::set_oracle_config(&env, &oracle_config);
 
         env.events().publish(
-            (symbol_short!("oracle_ad"),),
-            OracleAddressUpdated {
-                old_address,
-                new_address: new_oracle_address,
+            (symbol_short!("stale_cfg"),),
+            StalenessPolicyUpdated {
+                mode: oracle_config.staleness_mode,
+                max_age_seconds: max_age,
+                max_age_ledgers: oracle_config.max_oracle_age_ledgers,
+                admin,
             },
         );
 
         Ok(())
     }
 
-    fn set_max_oracle_age(env: Env, max_age: u64) -> Result<(), Error> {
+    fn set_oracle_staleness_policy(
+        env: Env,
+        mode: StalenessMode,
+        max_age_seconds: u64,
+        max_age_ledgers: u32,
+    ) -> Result<(), Error> {
+        oracle::validate_staleness_policy(mode, max_age_seconds, max_age_ledgers)?;
+
         let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
         admin.require_auth();
 
         let mut oracle_config = storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
-        oracle_config.max_oracle_age = max_age;
+        oracle_config.staleness_mode = mode;
+        oracle_config.max_oracle_age = max_age_seconds;
+        oracle_config.max_oracle_age_ledgers = max_age_ledgers;
 
         storage::set_oracle_config(&env, &oracle_config);
 
+        env.events().publish(
+            (symbol_short!("stale_cfg"),),
+            StalenessPolicyUpdated {
+                mode,
+                max_age_seconds,
+                max_age_ledgers,
+                admin,
+            },
+        );
+
         Ok(())
     }
 
@@ -387,9 +1653,87 @@ impl TimeLockTrait for TimeLockContract {
         Ok(())
     }
 
+    fn set_pair_max_slippage(
+        env: Env,
+        currency_pair: String,
+        slippage_bps: u32,
+    ) -> Result<(), Error> {
+        slippage::validate_slippage_bounds(slippage_bps)?;
+
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let old_slippage = storage::get_pair_slippage(&env, &currency_pair);
+        storage::set_pair_slippage(&env, &currency_pair, slippage_bps);
+
+        env.events().publish(
+            (symbol_short!("pslip_upd"),),
+            PairSlippageUpdated {
+                currency_pair,
+                old_slippage,
+                new_slippage: slippage_bps,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn set_circuit_breaker(
+        env: Env,
+        currency_pair: String,
+        max_change_bps: u32,
+        interval: u64,
+    ) -> Result<(), Error> {
+        let config = CircuitBreakerConfig {
+            max_change_bps,
+            interval,
+        };
+        circuit_breaker::validate_circuit_breaker_config(&config)?;
+
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        storage::set_circuit_breaker_config(&env, &currency_pair, &config);
+
+        env.events().publish(
+            (symbol_short!("cb_cfg"),),
+            CircuitBreakerUpdated {
+                currency_pair,
+                max_change_bps,
+                interval,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn reset_circuit_breaker(env: Env, currency_pair: String) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        storage::set_circuit_breaker_tripped(&env, &currency_pair, false);
+        storage::remove_reference_price(&env, &currency_pair);
+
+        env.events().publish(
+            (symbol_short!("cb_reset"),),
+            CircuitBreakerReset {
+                currency_pair,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn is_circuit_breaker_tripped(env: Env, currency_pair: String) -> Result<bool, Error> {
+        Ok(storage::is_circuit_breaker_tripped(&env, &currency_pair))
+    }
+
     fn check_exchange_rate(env: Env, currency_pair: String) -> Result<i128, Error> {
         let oracle_config = storage::get_oracle_config(&env).ok_or(Error::OracleUnavailable)?;
-        let oracle_rate = oracle::get_price(&env, &oracle_config)?;
+        let oracle_rate = oracle::get_price(&env, &oracle_config, &currency_pair)?;
 
         env.events().publish(
             (symbol_short!("price_q"),),
@@ -433,6 +1777,101 @@ impl TimeLockTrait for TimeLockContract {
         Ok(storage::get_total_fees(&env))
     }
 
+    fn set_insurance_config(env: Env, reserve_bps: u32, max_top_up: i128) -> Result<(), Error> {
+        let config = InsuranceConfig {
+            reserve_bps,
+            max_top_up,
+        };
+        insurance::validate_insurance_config(&config)?;
+
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        storage::set_insurance_config(&env, &config);
+
+        env.events().publish(
+            (symbol_short!("ins_cfg"),),
+            InsuranceConfigUpdated {
+                reserve_bps,
+                max_top_up,
+                admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_insurance_config(env: Env) -> Result<InsuranceConfig, Error> {
+        Ok(storage::get_insurance_config(&env))
+    }
+
+    fn get_insurance_reserve(env: Env) -> Result<i128, Error> {
+        Ok(storage::get_insurance_reserve(&env))
+    }
+
+    fn fund_insurance_reserve(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+        from.require_auth();
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer_from(
+            &env,
+            &usdc_address,
+            &from,
+            &env.current_contract_address(),
+            amount,
+        )?;
+
+        let reserve = storage::get_insurance_reserve(&env) + amount;
+        storage::set_insurance_reserve(&env, reserve);
+
+        env.events().publish(
+            (symbol_short!("ins_fund"),),
+            InsuranceReserveFunded {
+                gift_id: None,
+                amount,
+                reserve_balance: reserve,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn withdraw_insurance_reserve(env: Env, to: Address, amount: i128) -> Result<(), Error> {
+        let admin = storage::get_admin(&env).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+
+        let reserve = storage::get_insurance_reserve(&env);
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        // USDC committed to pending top-ups has already been swapped out
+        if amount > insurance::available_reserve(&env) {
+            return Err(Error::InsufficientFunds);
+        }
+
+        let usdc_address = storage::get_usdc_address(&env).ok_or(Error::InvalidTokenAddress)?;
+        token::transfer(&env, &usdc_address, &to, amount)?;
+
+        storage::set_insurance_reserve(&env, reserve - amount);
+
+        env.events().publish(
+            (symbol_short!("ins_wdr"),),
+            InsuranceReserveWithdrawn {
+                to,
+                amount,
+                reserve_balance: reserve - amount,
+            },
+        );
+
+        Ok(())
+    }
+
     /// Creates a gift from a verified USDC deposit (oracle-only).
     ///
     /// This function is called by the backend oracle after:
@@ -504,9 +1943,11 @@ impl TimeLockTrait for TimeLockContract {
             sender: env.current_contract_address(),
             recipient: None,
             amount,
+            withdrawn_amount: 0,
             unlock_timestamp,
             recipient_phone_hash: recipient_phone_hash.clone(),
             status: GiftStatus::Created,
+            kind: GiftKind::Standard,
         };
 
         storage::set_gift(&env, gift_id, &gift);
@@ -550,3 +1991,329 @@ impl TimeLockTrait for TimeLockContract {
         storage::get_payment_reference_gift_id(&env, &payment_reference).ok_or(Error::GiftNotFound)
     }
 }
+
+impl TimeLockContract {
+    /// Fail unless the gift can be withdrawn, then require its recipient's auth
+    fn require_recipient_auth(env: &Env, gift_id: u64) -> Result<(), Error> {
+        let gift = storage::get_gift(env, gift_id).ok_or(Error::GiftNotFound)?;
+        withdrawal::ensure_withdrawable(env, &gift)?;
+
+        let recipient = gift.recipient.as_ref().ok_or(Error::Unauthorized)?;
+        recipient.require_auth();
+
+        Ok(())
+    }
+
+    /// Verify the oracle attestation and bind the gift to `payout_address`, or to
+    /// `claimant` when none is given. The caller has already required the claimant's auth.
+    fn claim(
+        env: &Env,
+        claimant: &Address,
+        payout_address: Option<&Address>,
+        gift_id: u64,
+        verification_proof: &BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut gift = storage::get_gift(env, gift_id).ok_or(Error::GiftNotFound)?;
+
+        if gift.status != GiftStatus::Created {
+            return Err(if gift.status == GiftStatus::Claimed {
+                Error::AlreadyClaimed
+            } else {
+                Error::InvalidStatus
+            });
+        }
+
+        if env.ledger().timestamp() < gift.unlock_timestamp {
+            return Err(Error::NotUnlocked);
+        }
+        split::ensure_claimable(env, gift_id)?;
+
+        Self::verify_claim_proof(
+            env,
+            claimant,
+            payout_address,
+            &gift.recipient_phone_hash,
+            verification_proof,
+        );
+
+        let recipient = payout_address.unwrap_or(claimant).clone();
+        gift.recipient = Some(recipient.clone());
+        gift.status = GiftStatus::Unlocked;
+
+        storage::set_gift(env, gift_id, &gift);
+
+        env.events().publish(
+            (symbol_short!("claimed"),),
+            (gift_id, recipient, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    /// Verify the oracle's attestation that `claimant` (paying out to `payout_address`,
+    /// if given) owns the phone number behind `recipient_phone_hash`
+    fn verify_claim_proof(
+        env: &Env,
+        claimant: &Address,
+        payout_address: Option<&Address>,
+        recipient_phone_hash: &BytesN<32>,
+        verification_proof: &BytesN<64>,
+    ) {
+        let oracle_pk = storage::get_oracle_auth_key(env);
+
+        let mut payload = Bytes::new(env);
+        payload.append(&claimant.clone().to_xdr(env));
+        if let Some(payout_address) = payout_address {
+            payload.append(&payout_address.clone().to_xdr(env));
+        }
+        payload.append(&recipient_phone_hash.clone().to_xdr(env));
+
+        env.crypto()
+            .ed25519_verify(&oracle_pk, &payload, verification_proof);
+    }
+
+    /// Verify the oracle's attestation that the owner of the phone number behind
+    /// `recipient_phone_hash` declines gift `gift_id` of this contract
+    fn verify_rejection_proof(
+        env: &Env,
+        gift_id: u64,
+        recipient_phone_hash: &BytesN<32>,
+        attestation: &BytesN<64>,
+    ) {
+        let oracle_pk = storage::get_oracle_auth_key(env);
+
+        let mut payload = Bytes::new(env);
+        payload.append(&env.current_contract_address().to_xdr(env));
+        payload.append(&gift_id.to_xdr(env));
+        payload.append(&recipient_phone_hash.clone().to_xdr(env));
+
+        env.crypto().ed25519_verify(&oracle_pk, &payload, attestation);
+    }
+
+    /// Pay `amount` (the whole remainder when `None`) of an unlocked gift to its
+    /// recipient's wallet. The caller has already required the recipient's auth.
+    fn pay_to_wallet(env: &Env, gift_id: u64, amount: Option<i128>) -> Result<(), Error> {
+        let mut gift = storage::get_gift(env, gift_id).ok_or(Error::GiftNotFound)?;
+        withdrawal::ensure_withdrawable(env, &gift)?;
+        let amount = match amount {
+            Some(amount) => amount,
+            None => withdrawal::available_amount(env, &gift)?,
+        };
+        withdrawal::validate_withdrawal_amount(env, &gift, amount)?;
+
+        let recipient = gift.recipient.clone().ok_or(Error::Unauthorized)?;
+
+        let (fee_amount, amount_after_fee) = withdrawal::split_fee(amount);
+        let usdc_address = withdrawal::ensure_contract_balance(env, amount_after_fee)?;
+
+        // Internal Tracking: Collect Platform Fee
+        insurance::collect_fee(env, gift_id, fee_amount);
+
+        // Update internal accounting: Decrease total_held by the withdrawn amount
+        let total_held = storage::get_total_held(env) - amount;
+        storage::set_total_held(env, total_held);
+
+        gift.withdrawn_amount += amount;
+        let remaining_amount = withdrawal::remaining_amount(&gift);
+
+        // Events
+        env.events().publish(
+            (symbol_short!("fee_coll"),),
+            FeesCollected {
+                gift_id,
+                fee_amount_usdc: fee_amount,
+            },
+        );
+
+        env.events().publish(
+            (symbol_short!("withdr_s"),),
+            WithdrawalSuccess {
+                gift_id,
+                recipient: recipient.clone(),
+                amount_withdrawn: amount_after_fee,
+                fee_amount,
+                remaining_amount,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        // Transfer USDC to recipient
+        token::transfer(env, &usdc_address, &recipient, amount_after_fee)?;
+
+        if remaining_amount == 0 {
+            gift.status = GiftStatus::Withdrawn;
+        }
+        storage::set_gift(env, gift_id, &gift);
+
+        Ok(())
+    }
+
+    /// Escrow `amount` (the whole remainder when `None`) of an unlocked gift for an
+    /// anchor's bank payout. The caller has already required the recipient's auth.
+    fn start_bank_withdrawal(
+        env: &Env,
+        gift_id: u64,
+        amount: Option<i128>,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error> {
+        let BankPayout {
+            recipient_bank_details_hash,
+            anchor_address,
+            currency,
+            min_amount_out,
+            deadline,
+        } = payout;
+
+        let mut gift = storage::get_gift(env, gift_id).ok_or(Error::GiftNotFound)?;
+        withdrawal::ensure_withdrawable(env, &gift)?;
+        let amount = match amount {
+            Some(amount) => amount,
+            None => withdrawal::available_amount(env, &gift)?,
+        };
+        withdrawal::validate_withdrawal_amount(env, &gift, amount)?;
+
+        let now = env.ledger().timestamp();
+        if let Some(deadline) = deadline {
+            if now > deadline {
+                return Err(Error::DeadlineExceeded);
+            }
+        }
+        if let Some(min_amount_out) = min_amount_out {
+            if min_amount_out <= 0 {
+                return Err(Error::InvalidAmount);
+            }
+        }
+
+        let (fee_amount, amount_after_fee) = withdrawal::split_fee(amount);
+
+        let anchor_config = anchor::require_active_anchor(env, &anchor_address, &currency)?;
+        anchor::validate_payout_limits(&anchor_config, amount_after_fee)?;
+
+        let pricing = withdrawal::price_bank_withdrawal(env, &currency)?;
+        let oracle_rate = pricing.reading.rate;
+
+        // A firm quote from the anchor sets the rate and fee if it agrees with the oracle
+        let (rate, anchor_fee) = match &quote {
+            Some(signed) => {
+                anchor::verify_quote(
+                    env,
+                    &anchor_config,
+                    signed,
+                    &anchor_address,
+                    gift_id,
+                    amount_after_fee,
+                    &currency,
+                )?;
+                slippage::validate_slippage(
+                    env,
+                    &pricing.slippage_config,
+                    oracle_rate,
+                    signed.quote.rate,
+                )?;
+                (signed.quote.rate, signed.quote.fee)
+            }
+            None => (
+                oracle_rate,
+                anchor::calculate_anchor_fee(&anchor_config, amount_after_fee),
+            ),
+        };
+        // The anchor keeps its fee in USDC; only the rest is paid out in fiat
+        let payout_usdc = amount_after_fee - anchor_fee;
+        if payout_usdc <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let expected_amount_out = (payout_usdc * rate) / 1_000_000;
+
+        let effective_min_out = withdrawal::min_amount_out(
+            &pricing.slippage_config,
+            expected_amount_out,
+            min_amount_out,
+        );
+
+        let discovery = withdrawal::route_bank_withdrawal(
+            env,
+            &currency,
+            amount_after_fee,
+            payout_usdc,
+            expected_amount_out,
+            effective_min_out,
+        )?;
+
+        // Swap now so the anchor is paid at the rate checked above
+        let usdc_address = storage::get_usdc_address(env).ok_or(Error::InvalidTokenAddress)?;
+        let (payout_asset, mut payout_amount) = settlement::escrow_payout(
+            env,
+            &usdc_address,
+            &currency,
+            payout_usdc,
+            effective_min_out,
+            discovery,
+        )?;
+
+        // The insurance reserve makes up a swap that came in under the expected amount
+        let (top_up_usdc, top_up_amount) = if payout_asset != usdc_address {
+            insurance::cover_shortfall(
+                env,
+                &usdc_address,
+                &payout_asset,
+                &pricing.slippage_config,
+                payout_usdc,
+                expected_amount_out,
+                payout_amount,
+            )?
+        } else {
+            (0, 0)
+        };
+        payout_amount += top_up_amount;
+
+        oracle::record_price(env, &pricing.currency_pair, &pricing.reading);
+
+        env.events().publish(
+            (symbol_short!("bank_init"),),
+            BankWithdrawalInitiated {
+                gift_id,
+                anchor_address: anchor_address.clone(),
+                amount_usdc: amount_after_fee,
+                anchor_fee,
+                oracle_rate,
+                quote_id: quote.as_ref().map(|signed| signed.quote.quote_id.clone()),
+                quoted_rate: quote.as_ref().map(|signed| signed.quote.rate),
+                currency: currency.clone(),
+                expected_amount_out,
+                min_amount_out,
+                deadline,
+                effective_min_out,
+                timeout_at: now + constants::SETTLEMENT_TIMEOUT,
+            },
+        );
+
+        // Fee is only collected once the anchor confirms the payout
+        let pending = Settlement {
+            anchor_address: anchor_address.clone(),
+            amount_usdc: amount_after_fee,
+            fee_amount,
+            anchor_fee,
+            currency,
+            expected_amount_out,
+            min_amount_out: effective_min_out,
+            memo: recipient_bank_details_hash,
+            initiated_at: now,
+            nonce: storage::increment_next_settlement_nonce(env),
+            payout_asset,
+            payout_amount,
+            top_up_usdc,
+            top_up_amount,
+            held: false,
+        };
+        storage::set_settlement(env, gift_id, &pending);
+
+        let escrow = storage::get_anchor_escrow(env, &anchor_address) + amount_after_fee;
+        storage::set_anchor_escrow(env, &anchor_address, escrow);
+
+        gift.status = GiftStatus::PendingSettlement;
+        storage::set_gift(env, gift_id, &gift);
+
+        Ok(())
+    }
+}
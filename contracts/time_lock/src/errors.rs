@@ -13,7 +13,7 @@ pub enum Error {
     InvalidStatus = 6,
     InvalidProof = 7,
     OracleUnavailable = 8,
-    StaleOracleData = 9,
+    StaleOracleDataByTime = 9,
     InvalidExchangeRate = 10,
     SlippageExceeded = 11,
     InvalidSlippageConfig = 12,
@@ -27,4 +27,32 @@ pub enum Error {
     InvalidPaymentReference = 20,
     UnlockTimestampTooFar = 21,
   InvalidUnlockTime = 22,
+    CircuitBreakerTripped = 23,
+    InvalidCircuitBreakerConfig = 24,
+    StaleOracleDataByLedger = 25,
+    RouterNotConfigured = 26,
+    TooManyIntermediateAssets = 27,
+    DeadlineExceeded = 28,
+    SettlementNotFound = 29,
+    SettlementNotExpired = 30,
+    AnchorNotRegistered = 31,
+    AnchorDisabled = 32,
+    CurrencyNotSupported = 33,
+    PayoutOutsideAnchorLimits = 34,
+    InvalidAnchorConfig = 35,
+    InvalidQuote = 36,
+    QuoteExpired = 37,
+    QuoteAlreadyUsed = 38,
+    InvalidInsuranceConfig = 39,
+    RelayerNotAllowed = 40,
+    RelayFeeTooHigh = 41,
+    InvalidGiftTerms = 42,
+    InvalidGiftKind = 43,
+    LimitReached = 44,
+    ClaimPeriodNotOver = 45,
+    RedEnvelopeExhausted = 46,
+    InvalidStalenessPolicy = 47,
+    InvalidSwapPath = 48,
+    SwapFailed = 49,
+    ConfirmationAlreadyUsed = 50,
 }
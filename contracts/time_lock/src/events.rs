@@ -1,3 +1,7 @@
+use crate::anchor::AnchorConfig;
+use crate::oracle::StalenessMode;
+use crate::path_payment::PathQuote;
+use crate::types::Tranche;
 use soroban_sdk::{contracttype, Address, String, BytesN};
 
 #[contracttype]
@@ -33,28 +37,67 @@ pub struct OracleAddressUpdated {
     pub new_address: Address,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StalenessPolicyUpdated {
+    pub mode: StalenessMode,
+    pub max_age_seconds: u64,
+    pub max_age_ledgers: u32,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DexRouterUpdated {
+    pub old_router: Option<Address>,
+    pub new_router: Address,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BankWithdrawalInitiated {
     pub gift_id: u64,
+    pub anchor_address: Address,
     pub amount_usdc: i128,
-    pub expected_ngn: i128,
+    pub anchor_fee: i128,
+    pub oracle_rate: i128,
+    pub quote_id: Option<String>,
+    pub quoted_rate: Option<i128>,
+    pub currency: String,
+    pub expected_amount_out: i128,
+    pub min_amount_out: Option<i128>,
+    pub deadline: Option<u64>,
+    pub effective_min_out: i128,
+    pub timeout_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PairSlippageUpdated {
+    pub currency_pair: String,
+    pub old_slippage: Option<u32>,
+    pub new_slippage: u32,
+    pub admin: Address,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PathPaymentExecuted {
     pub usdc_sent: i128,
-    pub ngn_received: i128,
+    pub amount_out: i128,
+    pub currency: String,
     pub exchange_rate: i128,
     pub path: soroban_sdk::Vec<Address>,
+    pub quoted_amount_out: i128,
+    pub alternatives: soroban_sdk::Vec<PathQuote>,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AnchorDepositSent {
     pub anchor_address: Address,
-    pub ngn_amount: i128,
+    pub asset: Address,
+    pub amount: i128,
     pub memo: String,
 }
 
@@ -81,6 +124,8 @@ pub struct WithdrawalSuccess {
     pub gift_id: u64,
     pub recipient: Address,
     pub amount_withdrawn: i128,
+    pub fee_amount: i128,
+    pub remaining_amount: i128,
     pub timestamp: u64,
 }
 
@@ -90,3 +135,338 @@ pub struct FeeWithdrawal {
     pub total_fees: i128,
     pub to: Address,
 }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerTripped {
+    pub currency_pair: String,
+    pub previous_rate: i128,
+    pub new_rate: i128,
+    pub change_bps: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerUpdated {
+    pub currency_pair: String,
+    pub max_change_bps: u32,
+    pub interval: u64,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerReset {
+    pub currency_pair: String,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementConfirmed {
+    pub gift_id: u64,
+    pub anchor_address: Address,
+    pub anchor_tx_id: String,
+    pub amount_usdc: i128,
+    pub remaining_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementFailed {
+    pub gift_id: u64,
+    pub anchor_address: Address,
+    pub reason: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementExpired {
+    pub gift_id: u64,
+    pub anchor_address: Address,
+    pub amount_usdc: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementPayoutHeld {
+    pub gift_id: u64,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementUnwound {
+    pub gift_id: u64,
+    pub asset: Address,
+    pub amount: i128,
+    pub usdc_received: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementPayoutReleased {
+    pub gift_id: u64,
+    pub recipient: Address,
+    pub asset: Address,
+    pub amount: i128,
+    pub remaining_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnchorUpdated {
+    pub anchor_address: Address,
+    pub config: AnchorConfig,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceConfigUpdated {
+    pub reserve_bps: u32,
+    pub max_top_up: i128,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceReserveFunded {
+    pub gift_id: Option<u64>,
+    pub amount: i128,
+    pub reserve_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceReserveWithdrawn {
+    pub to: Address,
+    pub amount: i128,
+    pub reserve_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceTopUp {
+    pub gift_id: u64,
+    pub anchor_address: Address,
+    pub shortfall: i128,
+    pub top_up_usdc: i128,
+    pub reserve_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelayerUpdated {
+    pub relayer: Address,
+    pub allowed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelayFeeCapUpdated {
+    pub old_max_relay_fee: i128,
+    pub new_max_relay_fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimRelayed {
+    pub gift_id: u64,
+    pub relayer: Address,
+    pub recipient: Address,
+    pub relay_fee: i128,
+    pub remaining_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftScheduleCreated {
+    pub schedule_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+    pub interval: u64,
+    pub count: u32,
+    pub first_unlock: u64,
+    pub recipient_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstallmentIssued {
+    pub schedule_id: u64,
+    pub index: u32,
+    pub gift_id: u64,
+    pub unlock_timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftScheduleCancelled {
+    pub schedule_id: u64,
+    pub sender: Address,
+    pub refunded_installments: u32,
+    pub refund_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingGiftCreated {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+    pub recipient_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TranchedGiftCreated {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+    pub tranches: soroban_sdk::Vec<Tranche>,
+    pub recipient_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockedTranchesCancelled {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub cancelled_tranches: u32,
+    pub refund_amount: i128,
+    pub remaining_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GroupGiftOpened {
+    pub gift_id: u64,
+    pub organizer: Address,
+    pub unlock_timestamp: u64,
+    pub funding_deadline: u64,
+    pub recipient_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContributionReceived {
+    pub gift_id: u64,
+    pub contributor: Address,
+    pub amount: i128,
+    pub total_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GroupGiftClosed {
+    pub gift_id: u64,
+    pub amount: i128,
+    pub contributors: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContributionRefunded {
+    pub gift_id: u64,
+    pub contributor: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GroupGiftCancelled {
+    pub gift_id: u64,
+    pub refund_amount: i128,
+    pub contributors: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitGiftCreated {
+    pub split_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+    pub gift_ids: soroban_sdk::Vec<u64>,
+    pub unlock_timestamp: u64,
+    pub claim_deadline: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitSharesRefunded {
+    pub split_id: u64,
+    pub sender: Address,
+    pub refunded_shares: u32,
+    pub refund_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedEnvelopeCreated {
+    pub envelope_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+    pub max_claimers: u32,
+    pub min_share: i128,
+    pub restricted: bool,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedEnvelopeClaimed {
+    pub envelope_id: u64,
+    pub claimant: Address,
+    pub gift_id: u64,
+    pub share: i128,
+    pub claim_number: u32,
+    pub remaining: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedEnvelopeExhausted {
+    pub envelope_id: u64,
+    pub claimers: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedEnvelopeRefunded {
+    pub envelope_id: u64,
+    pub sender: Address,
+    pub refund_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftToppedUp {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub amount: i128,
+    pub new_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecipientUpdated {
+    pub gift_id: u64,
+    pub updated_by: Address,
+    pub old_phone_hash: BytesN<32>,
+    pub new_phone_hash: BytesN<32>,
+    pub change_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftRejected {
+    pub gift_id: u64,
+    pub sender: Address,
+    pub refunded_to: Option<Address>,
+    pub refund_amount: i128,
+    pub recipient_phone_hash: BytesN<32>,
+}
@@ -0,0 +1,94 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::events::ContributionRefunded;
+use crate::storage;
+use crate::token;
+use crate::types::{Gift, GiftStatus};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+
+/// Funding state of a group gift, kept alongside the gift while contributors pay in
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GroupGift {
+    pub organizer: Address,
+    pub funding_deadline: u64,      // Contributions close at this time
+    pub contributors: Vec<Address>, // Each contributor once, in order of first contribution
+}
+
+/// Validate the terms of a new group gift
+pub fn validate_group_gift(
+    env: &Env,
+    unlock_timestamp: u64,
+    funding_deadline: u64,
+) -> Result<(), Error> {
+    let current_time = env.ledger().timestamp();
+    if funding_deadline <= current_time || unlock_timestamp < funding_deadline {
+        return Err(Error::InvalidUnlockTime);
+    }
+    if unlock_timestamp - current_time > constants::MAX_LOCK_DURATION {
+        return Err(Error::UnlockTimestampTooFar);
+    }
+    Ok(())
+}
+
+/// Fail unless the group gift is still taking contributions
+pub fn ensure_collecting(env: &Env, gift: &Gift, group: &GroupGift) -> Result<(), Error> {
+    if gift.status != GiftStatus::Collecting {
+        return Err(Error::InvalidStatus);
+    }
+    if env.ledger().timestamp() > group.funding_deadline {
+        return Err(Error::DeadlineExceeded);
+    }
+    Ok(())
+}
+
+/// Record `amount` from `contributor`, adding them to the contributor list on first contribution
+pub fn record_contribution(
+    env: &Env,
+    gift_id: u64,
+    group: &mut GroupGift,
+    contributor: &Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    let previous = storage::get_contribution(env, gift_id, contributor);
+    if previous == 0 {
+        if group.contributors.len() >= constants::MAX_GROUP_CONTRIBUTORS {
+            return Err(Error::LimitReached);
+        }
+        group.contributors.push_back(contributor.clone());
+    }
+
+    let contribution = previous + amount;
+    storage::set_contribution(env, gift_id, contributor, contribution);
+    Ok(contribution)
+}
+
+/// Refund every contributor of a group gift. Returns the USDC refunded.
+pub fn refund_contributors(
+    env: &Env,
+    usdc_address: &Address,
+    gift_id: u64,
+    group: &GroupGift,
+) -> Result<i128, Error> {
+    let mut refunded = 0;
+    for contributor in group.contributors.iter() {
+        let amount = storage::get_contribution(env, gift_id, &contributor);
+        if amount == 0 {
+            continue;
+        }
+
+        storage::set_contribution(env, gift_id, &contributor, 0);
+        token::transfer(env, usdc_address, &contributor, amount)?;
+        refunded += amount;
+
+        env.events().publish(
+            (symbol_short!("grp_rfnd"),),
+            ContributionRefunded {
+                gift_id,
+                contributor,
+                amount,
+            },
+        );
+    }
+    Ok(refunded)
+}
@@ -0,0 +1,174 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::events::{InsuranceReserveFunded, InsuranceTopUp};
+use crate::path_payment;
+use crate::slippage::SlippageConfig;
+use crate::storage;
+use crate::types::Settlement;
+use crate::withdrawal;
+use soroban_sdk::{contracttype, symbol_short, Address, Env};
+
+/// Slippage-loss insurance configuration
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceConfig {
+    pub reserve_bps: u32, // Share of each collected fee routed to the reserve (0-10000)
+    pub max_top_up: i128, // Most USDC the reserve adds to a single payout
+}
+
+/// Default insurance configuration (disabled)
+pub fn default_insurance_config() -> InsuranceConfig {
+    InsuranceConfig {
+        reserve_bps: 0,
+        max_top_up: 0,
+    }
+}
+
+/// Validate insurance configuration
+pub fn validate_insurance_config(config: &InsuranceConfig) -> Result<(), Error> {
+    if config.reserve_bps > 10000 || config.max_top_up < 0 {
+        return Err(Error::InvalidInsuranceConfig);
+    }
+    Ok(())
+}
+
+/// Book a collected platform fee, routing the configured share into the reserve
+pub fn collect_fee(env: &Env, gift_id: u64, fee_amount: i128) {
+    let config = storage::get_insurance_config(env);
+    let reserve_share = (fee_amount * config.reserve_bps as i128) / 10000;
+
+    let total_fees = storage::get_total_fees(env) + fee_amount - reserve_share;
+    storage::set_total_fees(env, total_fees);
+
+    if reserve_share > 0 {
+        let reserve = storage::get_insurance_reserve(env) + reserve_share;
+        storage::set_insurance_reserve(env, reserve);
+
+        env.events().publish(
+            (symbol_short!("ins_fund"),),
+            InsuranceReserveFunded {
+                gift_id: Some(gift_id),
+                amount: reserve_share,
+                reserve_balance: reserve,
+            },
+        );
+    }
+}
+
+/// Reserve USDC not already committed to pending top-ups
+pub fn available_reserve(env: &Env) -> i128 {
+    storage::get_insurance_reserve(env) - storage::get_insurance_committed(env)
+}
+
+/// Top up a swapped payout that landed below the expected amount.
+///
+/// The shortfall is priced in USDC at the rate the withdrawal expected and
+/// capped by `max_top_up` and the uncommitted reserve. That USDC is swapped
+/// into `payout_asset` to be escrowed with the payout and stays committed
+/// until the settlement is confirmed or released.
+///
+/// Returns the USDC committed and the amount of `payout_asset` it bought.
+pub fn cover_shortfall(
+    env: &Env,
+    usdc_address: &Address,
+    payout_asset: &Address,
+    slippage_config: &SlippageConfig,
+    amount_usdc: i128,
+    expected_amount_out: i128,
+    amount_out: i128,
+) -> Result<(i128, i128), Error> {
+    let shortfall = expected_amount_out - amount_out;
+    if shortfall <= 0 || expected_amount_out <= 0 {
+        return Ok((0, 0));
+    }
+
+    let config = storage::get_insurance_config(env);
+    let shortfall_usdc = (shortfall * amount_usdc) / expected_amount_out;
+    let top_up_usdc = shortfall_usdc
+        .min(config.max_top_up)
+        .min(available_reserve(env));
+    if top_up_usdc <= 0 {
+        return Ok((0, 0));
+    }
+
+    let router = storage::get_dex_router(env).ok_or(Error::RouterNotConfigured)?;
+    let discovery =
+        path_payment::discover_optimal_path(env, &router, usdc_address, payout_asset, top_up_usdc)?;
+    let min_top_up_out = withdrawal::min_amount_out(
+        slippage_config,
+        (top_up_usdc * expected_amount_out) / amount_usdc,
+        None,
+    );
+    let swap_result = path_payment::execute_path_payment(
+        env,
+        &router,
+        top_up_usdc,
+        min_top_up_out,
+        &discovery.best,
+        env.ledger().timestamp() + constants::SWAP_DEADLINE_WINDOW,
+    )?;
+
+    let committed = storage::get_insurance_committed(env) + top_up_usdc;
+    storage::set_insurance_committed(env, committed);
+
+    Ok((top_up_usdc, swap_result.amount_out))
+}
+
+/// Debit a confirmed settlement's top-up from the reserve
+pub fn settle_top_up(env: &Env, gift_id: u64, settlement: &Settlement) {
+    if settlement.top_up_usdc <= 0 {
+        return;
+    }
+
+    let committed = storage::get_insurance_committed(env) - settlement.top_up_usdc;
+    storage::set_insurance_committed(env, committed);
+    let reserve = storage::get_insurance_reserve(env) - settlement.top_up_usdc;
+    storage::set_insurance_reserve(env, reserve);
+
+    env.events().publish(
+        (symbol_short!("ins_topup"),),
+        InsuranceTopUp {
+            gift_id,
+            anchor_address: settlement.anchor_address.clone(),
+            shortfall: settlement.expected_amount_out - settlement.payout_amount
+                + settlement.top_up_amount,
+            top_up_usdc: settlement.top_up_usdc,
+            reserve_balance: reserve,
+        },
+    );
+}
+
+/// Return a released settlement's top-up to the uncommitted reserve
+pub fn release_top_up(env: &Env, settlement: &Settlement) {
+    if settlement.top_up_usdc <= 0 {
+        return;
+    }
+
+    let committed = storage::get_insurance_committed(env) - settlement.top_up_usdc;
+    storage::set_insurance_committed(env, committed);
+}
+
+/// USDC the reserve and fee pool could absorb if a settlement's payout were
+/// swapped back at a loss, counting its top-up as already returned
+pub fn loss_capacity(env: &Env, settlement: &Settlement) -> i128 {
+    available_reserve(env) + settlement.top_up_usdc + storage::get_total_fees(env)
+}
+
+/// Book the gain or loss of swapping a released payout back to USDC. Gains go
+/// to the reserve; losses come out of the uncommitted reserve first and the
+/// fee pool after that.
+pub fn absorb_unwind(env: &Env, difference: i128) {
+    let from_reserve = if difference >= 0 {
+        -difference
+    } else {
+        (-difference).min(available_reserve(env).max(0))
+    };
+    let reserve = storage::get_insurance_reserve(env) - from_reserve;
+    storage::set_insurance_reserve(env, reserve);
+
+    let from_fees = -difference - from_reserve;
+    if from_fees > 0 {
+        let total_fees = storage::get_total_fees(env) - from_fees;
+        storage::set_total_fees(env, total_fees);
+    }
+}
@@ -1,8 +1,15 @@
+use crate::anchor::{AnchorConfig, SignedQuote};
 use crate::errors::Error;
-use crate::oracle::OracleConfig;
+use crate::group::GroupGift;
+use crate::insurance::InsuranceConfig;
+use crate::oracle::{OracleConfig, StalenessMode};
+use crate::red_envelope::RedEnvelope;
+use crate::schedule::{GiftSchedule, ScheduleInstallment};
 use crate::slippage::SlippageConfig;
-use crate::types::Gift;
-use soroban_sdk::{Address, BytesN, Env, String};
+use crate::split::{SplitGift, SplitShare};
+use crate::types::{Gift, Settlement, Tranche, VestingTerms};
+use crate::withdrawal::{BankPayout, WithdrawalMethod, WithdrawalPreview};
+use soroban_sdk::{Address, BytesN, Env, String, Vec};
 
 pub trait TimeLockTrait {
     /// Initialize contract with admin, oracle auth key (for claims), and oracle config (for price feed)
@@ -22,6 +29,160 @@ pub trait TimeLockTrait {
         recipient_phone_hash: BytesN<32>,
     ) -> Result<u64, Error>;
 
+    /// Create a gift that vests linearly from `terms.start` to `terms.end`. The recipient
+    /// can claim once the cliff has passed and withdraw what has vested so far.
+    fn create_vesting_gift(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        terms: VestingTerms,
+        recipient_phone_hash: BytesN<32>,
+    ) -> Result<u64, Error>;
+
+    /// Withdraw everything vested and not yet withdrawn from a claimed vesting gift,
+    /// with the fee charged on each withdrawal. Returns the USDC withdrawn before fees.
+    fn withdraw_vested(env: Env, gift_id: u64) -> Result<i128, Error>;
+
+    /// Create a milestone gift from (unlock time, amount) tranches. The recipient can
+    /// claim from the first unlock and withdraw each tranche once it unlocks.
+    fn create_tranched_gift(
+        env: Env,
+        sender: Address,
+        tranches: Vec<Tranche>,
+        recipient_phone_hash: BytesN<32>,
+    ) -> Result<u64, Error>;
+
+    /// Sender: Cancel the tranches of a milestone gift that are still locked and refund
+    /// them. Unlocked tranches stay with the gift. Returns the USDC refunded.
+    fn cancel_locked_tranches(env: Env, gift_id: u64) -> Result<i128, Error>;
+
+    /// Open a group gift that collects contributions until `funding_deadline`.
+    /// The gift stays `Collecting` and cannot be claimed until it is closed.
+    fn open_group_gift(
+        env: Env,
+        organizer: Address,
+        unlock_timestamp: u64,
+        funding_deadline: u64,
+        recipient_phone_hash: BytesN<32>,
+    ) -> Result<u64, Error>;
+
+    /// Contribute `amount` USDC to a collecting group gift via `transfer_from`
+    fn contribute(env: Env, gift_id: u64, contributor: Address, amount: i128) -> Result<(), Error>;
+
+    /// Stop collecting and make the group gift claimable. The organizer can close it
+    /// at any time; after the funding deadline anyone can.
+    fn close_group_gift(env: Env, gift_id: u64) -> Result<(), Error>;
+
+    /// Cancel a collecting group gift, refunding every contributor. The organizer can
+    /// cancel it at any time; after the funding deadline anyone can, so contributions
+    /// to a gift that was never closed are not stuck. Returns the USDC refunded.
+    fn cancel_group_gift(env: Env, gift_id: u64) -> Result<i128, Error>;
+
+    fn get_group_gift(env: Env, gift_id: u64) -> Result<GroupGift, Error>;
+
+    fn get_contribution(env: Env, gift_id: u64, contributor: Address) -> Result<i128, Error>;
+
+    /// Escrow one gift split between several recipients by `share_bps`. Each share is
+    /// issued as its own gift that its recipient claims, until `claim_deadline`, and
+    /// withdraws independently.
+    fn create_split_gift(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        unlock_timestamp: u64,
+        claim_deadline: u64,
+        shares: Vec<SplitShare>,
+    ) -> Result<u64, Error>;
+
+    /// Sender: After the claim deadline, refund the shares nobody has claimed.
+    /// Returns the USDC refunded.
+    fn refund_unclaimed_shares(env: Env, split_id: u64) -> Result<i128, Error>;
+
+    fn get_split_gift(env: Env, split_id: u64) -> Result<SplitGift, Error>;
+
+    /// Whether every share of a split gift has been withdrawn, refunded or rejected
+    fn is_split_gift_settled(env: Env, split_id: u64) -> Result<bool, Error>;
+
+    /// Create a red envelope: a pot split randomly among the first `max_claimers` to
+    /// claim, each drawing at least `min_share`. When `allowlist` is set, only those
+    /// phone hashes can claim, each with an oracle proof. Without one, claims are only
+    /// limited to one per address, so a single person using many addresses can drain
+    /// the whole pot.
+    fn create_red_envelope(
+        env: Env,
+        sender: Address,
+        amount: i128,
+        max_claimers: u32,
+        min_share: i128,
+        allowlist: Option<Vec<BytesN<32>>>,
+        expires_at: u64,
+    ) -> Result<u64, Error>;
+
+    /// Draw a random share of a red envelope. The share is issued as a gift already
+    /// claimed by `claimant`, withdrawable through the usual payout paths. Returns its ID.
+    fn claim_red_envelope(
+        env: Env,
+        envelope_id: u64,
+        claimant: Address,
+        recipient_phone_hash: Option<BytesN<32>>,
+        verification_proof: Option<BytesN<64>>,
+    ) -> Result<u64, Error>;
+
+    /// Sender: After expiry, reclaim what is left of a red envelope. Returns the USDC refunded.
+    fn refund_red_envelope(env: Env, envelope_id: u64) -> Result<i128, Error>;
+
+    fn get_red_envelope(env: Env, envelope_id: u64) -> Result<RedEnvelope, Error>;
+
+    /// Escrow `count` installments of `amount` up front. Installment `i` unlocks at
+    /// `first_unlock + i * interval` and is issued as its own gift once due.
+    fn create_gift_schedule(
+        env: Env,
+        sender: Address,
+        recipient_phone_hash: BytesN<32>,
+        amount: i128,
+        interval: u64,
+        count: u32,
+        first_unlock: u64,
+    ) -> Result<u64, Error>;
+
+    /// Keeper: Issue a gift for each installment whose unlock time has arrived.
+    /// Returns the IDs of the gifts issued.
+    fn issue_schedule_installments(env: Env, schedule_id: u64) -> Result<Vec<u64>, Error>;
+
+    /// Sender: Cancel a schedule, refunding the installments not yet unlocked.
+    /// Returns the USDC refunded.
+    fn cancel_gift_schedule(env: Env, schedule_id: u64) -> Result<i128, Error>;
+
+    fn get_gift_schedule(env: Env, schedule_id: u64) -> Result<GiftSchedule, Error>;
+
+    fn get_schedule_installments(
+        env: Env,
+        schedule_id: u64,
+    ) -> Result<Vec<ScheduleInstallment>, Error>;
+
+    /// Sender: Add `amount` USDC to a gift that has not been claimed yet
+    fn top_up_gift(env: Env, sender: Address, gift_id: u64, amount: i128) -> Result<(), Error>;
+
+    /// Sender: Point an unclaimed gift at a different phone hash. For gifts created by
+    /// `deposit_and_create_gift` the contract is the sender, so the oracle authorizes
+    /// the change instead. Limited to `MAX_RECIPIENT_CHANGES` per gift.
+    fn update_recipient(
+        env: Env,
+        sender: Address,
+        gift_id: u64,
+        new_phone_hash: BytesN<32>,
+    ) -> Result<(), Error>;
+
+    fn get_recipient_change_count(env: Env, gift_id: u64) -> Result<u32, Error>;
+
+    /// Decline an unclaimed gift, refunding whoever funded it in full. The oracle's
+    /// `attestation` signs this contract, the gift ID and the gift's phone hash once it
+    /// has verified the recipient, like a claim proof but bound to no address, so
+    /// anyone may submit it. Group gifts go back to their contributors and gifts from
+    /// `deposit_and_create_gift` to the oracle, which settles with the off-chain payer.
+    /// The event's `refunded_to` is who was refunded, or `None` for contributors.
+    fn reject_gift(env: Env, gift_id: u64, attestation: BytesN<64>) -> Result<(), Error>;
+
     fn claim_gift(
         env: Env,
         claimant: Address,
@@ -29,21 +190,187 @@ pub trait TimeLockTrait {
         verification_proof: BytesN<64>,
     ) -> Result<(), Error>;
 
+    /// Claim on behalf of a recipient: `claimant` submits and signs, `payout_address`
+    /// becomes the gift's recipient. The oracle proof covers (claimant, payout_address,
+    /// recipient_phone_hash).
+    fn claim_gift_for(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        payout_address: Address,
+        verification_proof: BytesN<64>,
+    ) -> Result<(), Error>;
+
+    /// Relayer: Submit a claim the recipient authorized, taking `relay_fee` USDC from
+    /// the gift as reimbursement. The recipient's auth covers the fee, which is capped
+    /// by `set_max_relay_fee`.
+    fn relay_claim(
+        env: Env,
+        relayer: Address,
+        recipient: Address,
+        gift_id: u64,
+        verification_proof: BytesN<64>,
+        relay_fee: i128,
+    ) -> Result<(), Error>;
+
+    /// Admin: Allow or disallow a relayer to submit claims
+    fn set_relayer_allowed(env: Env, relayer: Address, allowed: bool) -> Result<(), Error>;
+
+    fn is_relayer_allowed(env: Env, relayer: Address) -> Result<bool, Error>;
+
+    /// Admin: Set the most USDC a relayer may take from a gift per claim
+    fn set_max_relay_fee(env: Env, max_relay_fee: i128) -> Result<(), Error>;
+
+    fn get_max_relay_fee(env: Env) -> Result<i128, Error>;
+
+    /// Claim with the oracle proof and withdraw the whole gift to the claimant's
+    /// wallet in one call
+    fn claim_and_withdraw(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        verification_proof: BytesN<64>,
+    ) -> Result<(), Error>;
+
+    /// Claim with the oracle proof and start a bank withdrawal of the whole gift
+    /// in one call. Takes the same payout as `withdraw_to_bank`.
+    fn claim_and_withdraw_to_bank(
+        env: Env,
+        claimant: Address,
+        gift_id: u64,
+        verification_proof: BytesN<64>,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error>;
+
+    /// Escrow an unlocked gift for an anchor's bank payout and move it to
+    /// `PendingSettlement` until the anchor confirms or fails it. When the
+    /// currency has a payout token the USDC is swapped now and the output escrowed.
+    /// The payout's `min_amount_out` tightens the global slippage floor and its
+    /// `deadline` is the latest ledger timestamp at which the withdrawal may be
+    /// initiated. A firm `quote` signed by the anchor replaces the oracle rate when
+    /// it is within slippage bounds of it.
+    ///
+    /// An oracle reading beyond the pair's circuit breaker limit fails the
+    /// withdrawal with `CircuitBreakerTripped`, but the failed call stores
+    /// nothing, so the breaker itself is only tripped by `check_exchange_rate`.
     fn withdraw_to_bank(
         env: Env,
         gift_id: u64,
-        recipient_bank_details_hash: String,
-        anchor_address: Address,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error>;
+
+    /// Like `withdraw_to_bank` for `amount` USDC of the gift, with the fee charged
+    /// on that amount. The rest stays withdrawable once the payout settles.
+    fn withdraw_to_bank_partial(
+        env: Env,
+        gift_id: u64,
+        amount: i128,
+        payout: BankPayout,
+        quote: Option<SignedQuote>,
+    ) -> Result<(), Error>;
+
+    /// Confirm the bank payout with the anchor's signature over this contract, the gift,
+    /// the settlement's nonce, amount and start time, and `anchor_tx_id`, then release
+    /// the escrowed payout to the anchor. The USDC was already swapped to the payout
+    /// currency's token when the withdrawal started, if one is registered
+    fn confirm_settlement(
+        env: Env,
+        gift_id: u64,
+        anchor_tx_id: String,
+        anchor_signature: BytesN<64>,
     ) -> Result<(), Error>;
 
+    /// Anchor: Report a failed bank payout, returning the gift to `Unlocked`.
+    /// A swapped payout is swapped back to USDC with the round trip's gain or loss
+    /// booked to the insurance reserve. If that swap fails, the settlement is held in
+    /// escrow for `retry_settlement_release` or `accept_settlement_payout`.
+    fn fail_settlement(env: Env, gift_id: u64, reason: String) -> Result<(), Error>;
+
+    /// Return a settlement the anchor never answered to `Unlocked` once the timeout has
+    /// passed, releasing a swapped payout the same way as `fail_settlement`
+    fn expire_settlement(env: Env, gift_id: u64) -> Result<(), Error>;
+
+    /// Retry swapping a held settlement's payout back to USDC and return the gift to `Unlocked`
+    fn retry_settlement_release(env: Env, gift_id: u64) -> Result<(), Error>;
+
+    /// Recipient: Take a held settlement's payout token instead of waiting for USDC.
+    /// The withdrawal completes as if the anchor had confirmed it.
+    fn accept_settlement_payout(env: Env, gift_id: u64) -> Result<(), Error>;
+
+    fn get_settlement(env: Env, gift_id: u64) -> Result<Settlement, Error>;
+
+    /// Internal Tracking: USDC value escrowed for an anchor's pending settlements
+    fn get_anchor_escrow(env: Env, anchor: Address) -> Result<i128, Error>;
+
+    /// Admin: Register an anchor or replace its configuration
+    fn set_anchor(env: Env, anchor: Address, config: AnchorConfig) -> Result<(), Error>;
+
+    /// Admin: Enable or disable an anchor for new withdrawals
+    fn set_anchor_enabled(env: Env, anchor: Address, enabled: bool) -> Result<(), Error>;
+
+    fn get_anchor(env: Env, anchor: Address) -> Result<AnchorConfig, Error>;
+
+    /// List enabled anchors that pay out the given fiat currency
+    fn get_anchors_for_currency(env: Env, currency: String) -> Result<Vec<Address>, Error>;
+
     fn set_oracle_address(env: Env, new_oracle_address: Address) -> Result<(), Error>;
 
+    /// Admin: Set the DEX router used to swap USDC for bank withdrawals
+    fn set_dex_router(env: Env, router: Address) -> Result<(), Error>;
+
+    /// Admin: Set the token delivered to anchors for a payout currency (USDC is sent when unset)
+    fn set_payout_token(env: Env, currency: String, token: Address) -> Result<(), Error>;
+
+    /// Admin: Register an asset that swap paths may route through (e.g. XLM)
+    fn add_intermediate_asset(env: Env, asset: Address) -> Result<(), Error>;
+
+    /// Admin: Stop routing swap paths through an asset
+    fn remove_intermediate_asset(env: Env, asset: Address) -> Result<(), Error>;
+
+    fn get_intermediate_assets(env: Env) -> Result<Vec<Address>, Error>;
+
+    /// Admin: Set the time limit of the current staleness policy, which must stay valid
     fn set_max_oracle_age(env: Env, max_age: u64) -> Result<(), Error>;
 
+    /// Admin: Choose how oracle staleness is measured and the limits for each measure
+    fn set_oracle_staleness_policy(
+        env: Env,
+        mode: StalenessMode,
+        max_age_seconds: u64,
+        max_age_ledgers: u32,
+    ) -> Result<(), Error>;
+
     fn set_oracle_paused(env: Env, paused: bool) -> Result<(), Error>;
 
     fn set_max_slippage(env: Env, slippage_bps: u32) -> Result<(), Error>;
 
+    /// Admin: Override the slippage tolerance for one currency pair (e.g. "USDC/KES")
+    fn set_pair_max_slippage(
+        env: Env,
+        currency_pair: String,
+        slippage_bps: u32,
+    ) -> Result<(), Error>;
+
+    /// Admin: Configure the price movement circuit breaker for a currency pair
+    fn set_circuit_breaker(
+        env: Env,
+        currency_pair: String,
+        max_change_bps: u32,
+        interval: u64,
+    ) -> Result<(), Error>;
+
+    /// Admin: Reset a tripped circuit breaker so bank withdrawals can resume.
+    /// The next oracle reading becomes the pair's new reference price.
+    fn reset_circuit_breaker(env: Env, currency_pair: String) -> Result<(), Error>;
+
+    fn is_circuit_breaker_tripped(env: Env, currency_pair: String) -> Result<bool, Error>;
+
+    /// Get the rate for a currency pair, recording a fresh oracle reading.
+    /// A bank withdrawal that sees a reading beyond the circuit breaker limit
+    /// fails without storing anything; this call stores the reading and keeps
+    /// the breaker tripped until `reset_circuit_breaker`.
     fn check_exchange_rate(env: Env, currency_pair: String) -> Result<i128, Error>;
 
     fn validate_slippage(env: Env, oracle_rate: i128, actual_rate: i128) -> Result<(), Error>;
@@ -63,6 +390,23 @@ pub trait TimeLockTrait {
     /// Internal Tracking: Get accumulated platform fees
     fn get_total_fees(env: Env) -> Result<i128, Error>;
 
+    /// Admin: Set the share of each fee routed to the slippage insurance reserve
+    /// and the most the reserve adds to a single payout
+    fn set_insurance_config(env: Env, reserve_bps: u32, max_top_up: i128) -> Result<(), Error>;
+
+    fn get_insurance_config(env: Env) -> Result<InsuranceConfig, Error>;
+
+    /// Internal Tracking: USDC held in the slippage insurance reserve. Top-ups of
+    /// pending settlements are only debited once the anchor confirms them.
+    fn get_insurance_reserve(env: Env) -> Result<i128, Error>;
+
+    /// Admin: Add USDC to the insurance reserve from `from`, who must also authorize
+    /// the transfer (requires allowance)
+    fn fund_insurance_reserve(env: Env, from: Address, amount: i128) -> Result<(), Error>;
+
+    /// Admin: Withdraw USDC from the insurance reserve not committed to pending top-ups
+    fn withdraw_insurance_reserve(env: Env, to: Address, amount: i128) -> Result<(), Error>;
+
     /// Create gift from USDC deposit (oracle-only)
     /// Called by backend after Stripe payment confirmation and Anchor USDC deposit
     fn deposit_and_create_gift(
@@ -78,6 +422,22 @@ pub trait TimeLockTrait {
     /// Withdraw unlocked gift to recipient
     fn withdraw_gift(env: Env, gift_id: u64) -> Result<(), Error>;
 
+    /// Withdraw `amount` USDC of an unlocked gift, with the fee charged on that amount.
+    /// The gift becomes `Withdrawn` once fully drained.
+    fn withdraw_gift_partial(env: Env, gift_id: u64, amount: i128) -> Result<(), Error>;
+
+    /// Preview what `withdraw_gift` (Wallet) or `withdraw_to_bank` (Bank) would pay out
+    /// and which check would fail, without changing state. For a bank withdrawal
+    /// through `anchor`, its registered fee is deducted and its limits are checked;
+    /// without one the preview has no anchor fee.
+    fn preview_withdrawal(
+        env: Env,
+        gift_id: u64,
+        method: WithdrawalMethod,
+        currency: String,
+        anchor: Option<Address>,
+    ) -> Result<WithdrawalPreview, Error>;
+
     /// Admin: Withdraw accumulated fees
     fn withdraw_accumulated_fees(env: Env, to: Address) -> Result<(), Error>;
 }
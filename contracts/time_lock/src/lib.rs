@@ -1,18 +1,36 @@
 #![no_std]
 
+mod anchor;
+mod circuit_breaker;
 mod constants;
 mod contract;
 pub mod errors;
 pub mod events;
+mod group;
+mod insurance;
 pub mod interface;
 mod oracle;
 mod path_payment;
+mod red_envelope;
+mod schedule;
+mod settlement;
 mod slippage;
+mod split;
 mod storage;
 mod token;
 mod test;
+mod tranche;
 pub mod types;
+mod vesting;
+mod withdrawal;
 
+pub use anchor::{AnchorConfig, FirmQuote, SignedQuote};
 pub use contract::TimeLockContract;
 pub use contract::TimeLockContractClient;
+pub use group::GroupGift;
+pub use insurance::InsuranceConfig;
 pub use interface::TimeLockTrait;
+pub use red_envelope::RedEnvelope;
+pub use schedule::{GiftSchedule, InstallmentStatus, ScheduleInstallment, ScheduleStatus};
+pub use split::{SplitGift, SplitShare};
+pub use withdrawal::{BankPayout, WithdrawalMethod, WithdrawalPreview};
@@ -1,3 +1,5 @@
+use crate::circuit_breaker;
+use crate::constants;
 use crate::errors::Error;
 use crate::storage;
 use crate::types::PriceCache;
@@ -12,58 +14,204 @@ pub struct PriceData {
     pub source: String, // Price source identifier
 }
 
+/// Which age limits decide whether oracle data is stale
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StalenessMode {
+    Seconds, // Only `max_oracle_age` (ledger timestamp) is checked
+    Ledgers, // Only `max_oracle_age_ledgers` (ledger sequence) is checked
+    Both,    // Data must be fresh by both measures
+}
+
 /// Oracle configuration
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct OracleConfig {
-    pub oracle_address: Address, // Oracle contract address
-    pub max_oracle_age: u64,     // Max age of oracle data in ledger entries
-    pub is_paused: bool,         // Whether oracle checks are paused
+    pub oracle_address: Address,       // Oracle contract address
+    pub max_oracle_age: u64,           // Max age of oracle data in seconds
+    pub max_oracle_age_ledgers: u32,   // Max age of oracle data in ledgers
+    pub staleness_mode: StalenessMode, // Which of the two limits apply
+    pub is_paused: bool,               // Whether oracle checks are paused
 }
 
 /// Default oracle configuration
 pub fn default_oracle_config(oracle_address: Address) -> OracleConfig {
     OracleConfig {
         oracle_address,
-        max_oracle_age: 300, // 5 minutes (300 ledger entries)
+        max_oracle_age: 300,        // 5 minutes
+        max_oracle_age_ledgers: 60, // ~5 minutes at 5s per ledger
+        staleness_mode: StalenessMode::Seconds,
         is_paused: false,
     }
 }
 
-/// Get price from oracle or cache
-pub fn get_price(env: &Env, config: &OracleConfig) -> Result<i128, Error> {
+/// Validate a staleness policy: every limit the mode checks must be non-zero
+pub fn validate_staleness_policy(
+    mode: StalenessMode,
+    max_age_seconds: u64,
+    max_age_ledgers: u32,
+) -> Result<(), Error> {
+    let valid = match mode {
+        StalenessMode::Seconds => max_age_seconds > 0,
+        StalenessMode::Ledgers => max_age_ledgers > 0,
+        StalenessMode::Both => max_age_seconds > 0 && max_age_ledgers > 0,
+    };
+    if !valid {
+        return Err(Error::InvalidStalenessPolicy);
+    }
+    Ok(())
+}
+
+/// Oracle currency pair for a fiat payout currency, e.g. "NGN" -> "USDC/NGN"
+pub fn currency_pair(env: &Env, currency: &String) -> Result<String, Error> {
+    let prefix = constants::CURRENCY_PAIR_PREFIX.as_bytes();
+    let code_len = currency.len() as usize;
+    if code_len == 0 || code_len > constants::MAX_CURRENCY_CODE_LEN {
+        return Err(Error::CurrencyNotSupported);
+    }
+
+    let mut buf = [0u8; constants::CURRENCY_PAIR_PREFIX.len() + constants::MAX_CURRENCY_CODE_LEN];
+    let pair_len = prefix.len() + code_len;
+    buf[..prefix.len()].copy_from_slice(prefix);
+    currency.copy_into_slice(&mut buf[prefix.len()..pair_len]);
+
+    let pair = core::str::from_utf8(&buf[..pair_len]).map_err(|_| Error::CurrencyNotSupported)?;
+    Ok(String::from_str(env, pair))
+}
+
+/// Rate for a currency pair, with the oracle reading still to be recorded
+/// when the cache was stale
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceReading {
+    pub rate: i128,
+    pub fresh: Option<PriceCache>,
+}
+
+/// Get price for a currency pair from oracle or cache
+///
+/// A fresh reading that moves more than the pair's circuit breaker allows is
+/// still cached, but the pair is marked as tripped. Callers that pay out at
+/// this rate must check `circuit_breaker::ensure_not_tripped` afterwards.
+pub fn get_price(env: &Env, config: &OracleConfig, currency_pair: &String) -> Result<i128, Error> {
+    let reading = read_price(env, config, currency_pair)?;
+    record_price(env, currency_pair, &reading);
+    Ok(reading.rate)
+}
+
+/// Get price for a currency pair from oracle or cache without writing to storage
+pub fn read_price(
+    env: &Env,
+    config: &OracleConfig,
+    currency_pair: &String,
+) -> Result<PriceReading, Error> {
     if config.is_paused {
         return Err(Error::OraclePaused);
     }
 
-    if let Some(cached) = storage::get_price_cache(env) {
-        if validate_data_freshness(env.ledger().timestamp(), cached.timestamp, config.max_oracle_age).is_ok() {
-            return Ok(cached.rate);
+    if let Some(cached) = storage::get_price_cache(env, currency_pair) {
+        if validate_data_freshness(
+            config,
+            env.ledger().timestamp(),
+            env.ledger().sequence(),
+            &cached,
+        )
+        .is_ok()
+        {
+            return Ok(PriceReading {
+                rate: cached.rate,
+                fresh: None,
+            });
         }
     }
 
-    let oracle_rate: i128 = 1_000_000;
-    let current_timestamp = env.ledger().timestamp();
+    let oracle_rate = fetch_oracle_rate(env, config, currency_pair)?;
 
     validate_rate_bounds(oracle_rate)?;
 
-    let cache_entry = PriceCache {
+    Ok(PriceReading {
         rate: oracle_rate,
-        timestamp: current_timestamp,
-    };
-    storage::set_price_cache(env, &cache_entry);
+        fresh: Some(PriceCache {
+            rate: oracle_rate,
+            timestamp: env.ledger().timestamp(),
+            ledger: env.ledger().sequence(),
+        }),
+    })
+}
 
-    Ok(oracle_rate)
+/// Cache a fresh reading and run it past the pair's circuit breaker
+pub fn record_price(env: &Env, currency_pair: &String, reading: &PriceReading) {
+    if let Some(fresh) = &reading.fresh {
+        circuit_breaker::check_reading(env, currency_pair, fresh);
+        storage::set_price_cache(env, currency_pair, fresh);
+    }
 }
 
-/// Validate oracle data freshness
+/// Fail if the pair's breaker is tripped or recording this reading would trip it.
+/// The trip is not recorded here: the caller's failure rolls everything back.
+pub fn ensure_tradable(
+    env: &Env,
+    currency_pair: &String,
+    reading: &PriceReading,
+) -> Result<(), Error> {
+    circuit_breaker::ensure_not_tripped(env, currency_pair)?;
+    if let Some(fresh) = &reading.fresh {
+        if circuit_breaker::exceeded_change(env, currency_pair, fresh.rate).is_some() {
+            return Err(Error::CircuitBreakerTripped);
+        }
+    }
+    Ok(())
+}
+
+/// Fetch a new reading from the oracle
+fn fetch_oracle_rate(
+    _env: &Env,
+    _config: &OracleConfig,
+    _currency_pair: &String,
+) -> Result<i128, Error> {
+    Ok(1_000_000)
+}
+
+/// Validate oracle data freshness according to the configured staleness mode
 pub fn validate_data_freshness(
+    config: &OracleConfig,
+    current_timestamp: u64,
+    current_ledger: u32,
+    data: &PriceCache,
+) -> Result<(), Error> {
+    match config.staleness_mode {
+        StalenessMode::Seconds => {
+            validate_timestamp_freshness(current_timestamp, data.timestamp, config.max_oracle_age)
+        }
+        StalenessMode::Ledgers => {
+            validate_ledger_freshness(current_ledger, data.ledger, config.max_oracle_age_ledgers)
+        }
+        StalenessMode::Both => {
+            validate_timestamp_freshness(current_timestamp, data.timestamp, config.max_oracle_age)?;
+            validate_ledger_freshness(current_ledger, data.ledger, config.max_oracle_age_ledgers)
+        }
+    }
+}
+
+/// Validate oracle data age in seconds
+pub fn validate_timestamp_freshness(
     current_timestamp: u64,
     data_timestamp: u64,
     max_age: u64,
 ) -> Result<(), Error> {
     if current_timestamp.saturating_sub(data_timestamp) > max_age {
-        return Err(Error::StaleOracleData);
+        return Err(Error::StaleOracleDataByTime);
+    }
+    Ok(())
+}
+
+/// Validate oracle data age in ledgers
+pub fn validate_ledger_freshness(
+    current_ledger: u32,
+    data_ledger: u32,
+    max_age_ledgers: u32,
+) -> Result<(), Error> {
+    if current_ledger.saturating_sub(data_ledger) > max_age_ledgers {
+        return Err(Error::StaleOracleDataByLedger);
     }
     Ok(())
 }
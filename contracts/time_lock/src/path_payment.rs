@@ -1,50 +1,214 @@
-use soroban_sdk::{Env, Address, Vec};
+use crate::constants;
 use crate::errors::Error;
+use crate::storage;
+use crate::token;
+use soroban_sdk::{contractclient, contracterror, contracttype, Address, Env, InvokeError, Vec};
+
+/// Errors a router reports, by contract error code
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum RouterError {
+    NoPool = 1,                   // No pool for a hop of the path
+    InsufficientLiquidity = 2,    // A pool cannot fill the swap
+    InsufficientOutputAmount = 3, // Output fell below `amount_out_min`
+    Expired = 4,                  // Ledger timestamp passed the deadline
+}
+
+/// Soroswap-style DEX router
+///
+/// The router pulls `amount_in` of `path[0]` from `to` using an allowance
+/// and sends the output of the last hop back to `to`.
+#[allow(dead_code)]
+#[contractclient(name = "RouterClient")]
+pub trait Router {
+    /// Quote the amounts produced at each hop of `path` for `amount_in`
+    fn router_get_amounts_out(
+        env: Env,
+        amount_in: i128,
+        path: Vec<Address>,
+    ) -> Result<Vec<i128>, RouterError>;
+
+    /// Swap `amount_in` along `path`, failing if the output is below `amount_out_min`
+    /// or the ledger timestamp is past `deadline`
+    fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, RouterError>;
+}
 
 pub struct PathPaymentOutput {
     pub amount_in: i128,
     pub amount_out: i128,
 }
 
+/// Router quote for one candidate path
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PathQuote {
+    pub path: Vec<Address>,
+    pub amount_out: i128,
+}
+
+/// Best quoted path plus the quotes of every other viable candidate
+pub struct PathDiscovery {
+    pub best: PathQuote,
+    pub alternatives: Vec<PathQuote>,
+}
+
+/// Quote every candidate path through the registered intermediate assets
+/// and pick the one with the highest output
 pub fn discover_optimal_path(
     env: &Env,
-    _token_in: &Address,
-    _token_out: &Address,
+    router: &Address,
+    token_in: &Address,
+    token_out: &Address,
     amount_in: i128,
-) -> Result<Vec<Address>, Error> {
-    if amount_in > 100_000_000 {
-        return Err(Error::InsufficientLiquidity);
+) -> Result<PathDiscovery, Error> {
+    let intermediates = storage::get_intermediate_assets(env);
+    let mut candidates = Vec::new(env);
+    let mut prefix = Vec::new(env);
+    prefix.push_back(token_in.clone());
+    collect_candidate_paths(
+        &prefix,
+        token_out,
+        &intermediates,
+        constants::MAX_PATH_HOPS,
+        &mut candidates,
+    );
+
+    let client = RouterClient::new(env, router);
+    let mut best: Option<PathQuote> = None;
+    let mut alternatives = Vec::new(env);
+
+    for path in candidates.iter() {
+        // Paths the router cannot quote (no pool, shallow pool) are skipped
+        let amount_out = match client.try_router_get_amounts_out(&amount_in, &path) {
+            Ok(Ok(amounts)) => match amounts.last() {
+                Some(amount_out) => amount_out,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let quote = PathQuote { path, amount_out };
+
+        match best {
+            Some(current) if current.amount_out >= quote.amount_out => {
+                alternatives.push_back(quote);
+                best = Some(current);
+            }
+            Some(current) => {
+                alternatives.push_back(current);
+                best = Some(quote);
+            }
+            None => best = Some(quote),
+        }
     }
 
-    let mut path = Vec::new(env);
-    // In a real DEX, this might be [USDC, XLM, NGN]
-    path.push_back(_token_in.clone());
-    path.push_back(_token_out.clone());
-    
-    Ok(path)
+    let best = best.ok_or(Error::InsufficientLiquidity)?;
+    Ok(PathDiscovery { best, alternatives })
 }
 
+/// Enumerate paths from the last asset of `prefix` to `token_out` with at most `max_hops` hops
+fn collect_candidate_paths(
+    prefix: &Vec<Address>,
+    token_out: &Address,
+    intermediates: &Vec<Address>,
+    max_hops: u32,
+    candidates: &mut Vec<Vec<Address>>,
+) {
+    let mut direct = prefix.clone();
+    direct.push_back(token_out.clone());
+    candidates.push_back(direct);
+
+    // Inserting one more intermediate asset adds one hop
+    if prefix.len() + 1 > max_hops {
+        return;
+    }
+
+    for asset in intermediates.iter() {
+        if asset == *token_out || prefix.contains(&asset) {
+            continue;
+        }
+        let mut next = prefix.clone();
+        next.push_back(asset);
+        collect_candidate_paths(&next, token_out, intermediates, max_hops, candidates);
+    }
+}
+
+/// Swap through the router along a quoted path, delivering the output to this contract
 pub fn execute_path_payment(
-    _env: &Env,
+    env: &Env,
+    router: &Address,
     amount_in: i128,
     min_amount_out: i128,
-    path: Vec<Address>,
-    _anchor: &Address,
+    quote: &PathQuote,
+    deadline: u64,
 ) -> Result<PathPaymentOutput, Error> {
+    let path = &quote.path;
     if path.len() < 2 {
-        return Err(Error::OracleUnavailable);
+        return Err(Error::InvalidSwapPath);
     }
-    
-    let simulated_rate: i128 = 990_000;
-    let amount_out = (amount_in * simulated_rate) / 1_000_000;
 
+    // Check the quote first so a bad price is not reported as a router failure
+    if quote.amount_out < min_amount_out {
+        return Err(Error::SlippageExceeded);
+    }
+
+    let token_in = path.get(0).ok_or(Error::InvalidTokenAddress)?;
+    token::approve(
+        env,
+        &token_in,
+        router,
+        amount_in,
+        env.ledger().sequence(),
+    )?;
+
+    let client = RouterClient::new(env, router);
+    let contract_address = env.current_contract_address();
+    let amounts = match client.try_swap_exact_tokens_for_tokens(
+        &amount_in,
+        &min_amount_out,
+        path,
+        &contract_address,
+        &deadline,
+    ) {
+        Ok(Ok(amounts)) => amounts,
+        Ok(Err(_)) => return Err(Error::SwapFailed),
+        Err(err) => return Err(router_error(err)),
+    };
+
+    let amount_out = last_amount(&amounts)?;
     if amount_out < min_amount_out {
         return Err(Error::SlippageExceeded);
     }
 
-    
     Ok(PathPaymentOutput {
         amount_in,
         amount_out,
     })
 }
+
+/// Map a failed router call to the contract error reported to the caller.
+///
+/// Only a pool that cannot fill the swap is a liquidity problem and output
+/// below the minimum is slippage; an expired deadline or unknown path keep
+/// their own errors, and anything else the router or host rejected (e.g. a
+/// failed token authorization) is a failed swap.
+pub fn router_error(err: Result<RouterError, InvokeError>) -> Error {
+    match err {
+        Ok(RouterError::InsufficientLiquidity) => Error::InsufficientLiquidity,
+        Ok(RouterError::InsufficientOutputAmount) => Error::SlippageExceeded,
+        Ok(RouterError::Expired) => Error::DeadlineExceeded,
+        Ok(RouterError::NoPool) => Error::InvalidSwapPath,
+        Err(_) => Error::SwapFailed,
+    }
+}
+
+fn last_amount(amounts: &Vec<i128>) -> Result<i128, Error> {
+    amounts.last().ok_or(Error::InsufficientLiquidity)
+}
@@ -0,0 +1,80 @@
+use crate::constants;
+use crate::errors::Error;
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Pot split randomly among the first `max_claimers` people to claim
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedEnvelope {
+    pub sender: Address,
+    pub amount: i128,                       // USDC in the pot
+    pub remaining: i128,                    // USDC not yet drawn
+    pub max_claimers: u32,                  // Number of shares the pot is split into
+    pub min_share: i128,                    // Least USDC any claimer draws
+    pub allowlist: Option<Vec<BytesN<32>>>, // Phone hashes allowed to claim, if restricted
+    pub claimers: Vec<Address>,             // Addresses that have claimed, in order
+    pub claimed_hashes: Vec<BytesN<32>>,    // Allowlisted phone hashes already used
+    pub gift_ids: Vec<u64>,                 // Gift issued for each claim
+    pub expires_at: u64,                    // The sender can reclaim what is left after this
+}
+
+/// Validate the terms of a new red envelope
+pub fn validate_red_envelope(
+    env: &Env,
+    amount: i128,
+    max_claimers: u32,
+    min_share: i128,
+    allowlist: &Option<Vec<BytesN<32>>>,
+    expires_at: u64,
+) -> Result<(), Error> {
+    if !(constants::MIN_GIFT_AMOUNT..=constants::MAX_GIFT_AMOUNT).contains(&amount) {
+        return Err(Error::InvalidAmount);
+    }
+    if max_claimers == 0 || max_claimers > constants::MAX_ENVELOPE_CLAIMERS || min_share <= 0 {
+        return Err(Error::InvalidGiftTerms);
+    }
+    if min_share * max_claimers as i128 > amount {
+        return Err(Error::InvalidGiftTerms);
+    }
+    if let Some(allowlist) = allowlist {
+        if allowlist.is_empty() || allowlist.len() > constants::MAX_ENVELOPE_CLAIMERS {
+            return Err(Error::InvalidGiftTerms);
+        }
+    }
+
+    let current_time = env.ledger().timestamp();
+    if expires_at <= current_time {
+        return Err(Error::InvalidUnlockTime);
+    }
+    if expires_at - current_time > constants::MAX_LOCK_DURATION {
+        return Err(Error::UnlockTimestampTooFar);
+    }
+
+    Ok(())
+}
+
+/// Draw the next claimer's share of the pot.
+///
+/// Each claimer gets at least `min_share` and at most twice the average of what
+/// is left, while leaving `min_share` for every later claimer. The last claimer
+/// receives the remainder.
+pub fn draw_share(env: &Env, envelope: &RedEnvelope) -> i128 {
+    let claimers_left = (envelope.max_claimers - envelope.claimers.len()) as i128;
+    if claimers_left <= 1 {
+        return envelope.remaining;
+    }
+
+    let reserved = envelope.min_share * (claimers_left - 1);
+    let high = (envelope.remaining - reserved).min(2 * envelope.remaining / claimers_left);
+    let low = envelope.min_share;
+    if high <= low {
+        return low;
+    }
+
+    env.prng().gen_range::<u64>(low as u64..=high as u64) as i128
+}
+
+/// Whether every share of the envelope has been drawn
+pub fn is_exhausted(envelope: &RedEnvelope) -> bool {
+    envelope.remaining == 0 || envelope.claimers.len() >= envelope.max_claimers
+}
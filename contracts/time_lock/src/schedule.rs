@@ -0,0 +1,227 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::events::{GiftCreated, GiftScheduleCancelled, InstallmentIssued};
+use crate::storage;
+use crate::token;
+use crate::types::{Gift, GiftKind, GiftStatus};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Symbol, Vec};
+
+/// Lifecycle of a recurring gift schedule
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScheduleStatus {
+    Active,    // Installments still to be issued
+    Completed, // Every installment issued as a gift
+    Cancelled, // Sender cancelled; unissued installments refunded
+}
+
+/// Recurring gift: `count` installments of `amount`, one every `interval` seconds
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GiftSchedule {
+    pub sender: Address,
+    pub recipient_phone_hash: BytesN<32>,
+    pub amount: i128,       // USDC per installment
+    pub interval: u64,      // Seconds between installment unlocks
+    pub count: u32,         // Number of installments
+    pub first_unlock: u64,  // Unlock time of the first installment
+    pub gift_ids: Vec<u64>, // Gifts issued so far, in installment order
+    pub status: ScheduleStatus,
+}
+
+/// Where a single installment of a schedule stands
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InstallmentStatus {
+    Scheduled,          // Not yet unlocked or issued
+    Issued(GiftStatus), // Issued as a gift, currently in this status
+    Cancelled,          // Refunded to the sender
+}
+
+/// One installment of a schedule, as returned by `get_schedule_installments`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduleInstallment {
+    pub index: u32,
+    pub unlock_timestamp: u64,
+    pub gift_id: Option<u64>, // Gift issued for the installment, if any
+    pub status: InstallmentStatus,
+}
+
+/// Validate the terms of a new schedule
+pub fn validate_schedule(
+    env: &Env,
+    amount: i128,
+    interval: u64,
+    count: u32,
+    first_unlock: u64,
+) -> Result<(), Error> {
+    if !(constants::MIN_GIFT_AMOUNT..=constants::MAX_GIFT_AMOUNT).contains(&amount) {
+        return Err(Error::InvalidAmount);
+    }
+    if interval == 0 || count == 0 || count > constants::MAX_SCHEDULE_INSTALLMENTS {
+        return Err(Error::InvalidGiftTerms);
+    }
+
+    let current_time = env.ledger().timestamp();
+    if first_unlock <= current_time {
+        return Err(Error::InvalidUnlockTime);
+    }
+    let last_unlock = interval
+        .checked_mul(count as u64 - 1)
+        .and_then(|span| first_unlock.checked_add(span))
+        .ok_or(Error::InvalidGiftTerms)?;
+    if last_unlock - current_time > constants::MAX_LOCK_DURATION {
+        return Err(Error::UnlockTimestampTooFar);
+    }
+
+    Ok(())
+}
+
+/// Unlock time of the installment at `index`
+pub fn installment_unlock(schedule: &GiftSchedule, index: u32) -> Result<u64, Error> {
+    schedule
+        .interval
+        .checked_mul(index as u64)
+        .and_then(|offset| schedule.first_unlock.checked_add(offset))
+        .ok_or(Error::InvalidGiftTerms)
+}
+
+/// Total USDC escrowed for a schedule
+pub fn total_amount(schedule: &GiftSchedule) -> Result<i128, Error> {
+    schedule
+        .amount
+        .checked_mul(schedule.count as i128)
+        .ok_or(Error::InvalidGiftTerms)
+}
+
+/// Issue a gift for every installment whose unlock time has arrived.
+/// Returns the IDs of the gifts issued by this call.
+pub fn issue_due_installments(
+    env: &Env,
+    schedule_id: u64,
+    schedule: &mut GiftSchedule,
+) -> Result<Vec<u64>, Error> {
+    let mut issued = Vec::new(env);
+    if schedule.status != ScheduleStatus::Active {
+        return Ok(issued);
+    }
+
+    let current_time = env.ledger().timestamp();
+    while schedule.gift_ids.len() < schedule.count {
+        let index = schedule.gift_ids.len();
+        let unlock_timestamp = installment_unlock(schedule, index)?;
+        if unlock_timestamp > current_time {
+            break;
+        }
+
+        let gift_id = storage::increment_next_gift_id(env);
+        let gift = Gift {
+            sender: schedule.sender.clone(),
+            recipient: None,
+            amount: schedule.amount,
+            withdrawn_amount: 0,
+            unlock_timestamp,
+            recipient_phone_hash: schedule.recipient_phone_hash.clone(),
+            status: GiftStatus::Created,
+            kind: GiftKind::Standard,
+        };
+        storage::set_gift(env, gift_id, &gift);
+
+        let total_gifted = storage::get_total_gifted(env) + schedule.amount;
+        storage::set_total_gifted(env, total_gifted);
+
+        env.events().publish(
+            (Symbol::new(env, "gift_created"),),
+            GiftCreated {
+                gift_id,
+                sender: schedule.sender.clone(),
+                amount: schedule.amount,
+                unlock_time: unlock_timestamp,
+                recipient_hash: schedule.recipient_phone_hash.clone(),
+            },
+        );
+        env.events().publish(
+            (symbol_short!("sched_iss"),),
+            InstallmentIssued {
+                schedule_id,
+                index,
+                gift_id,
+                unlock_timestamp,
+            },
+        );
+
+        schedule.gift_ids.push_back(gift_id);
+        issued.push_back(gift_id);
+    }
+
+    if schedule.gift_ids.len() == schedule.count {
+        schedule.status = ScheduleStatus::Completed;
+    }
+
+    Ok(issued)
+}
+
+/// Cancel a schedule, refunding every installment not yet unlocked to the sender.
+/// Installments already due are issued first. Returns the USDC refunded.
+pub fn cancel(env: &Env, schedule_id: u64, schedule: &mut GiftSchedule) -> Result<i128, Error> {
+    if schedule.status != ScheduleStatus::Active {
+        return Err(Error::InvalidStatus);
+    }
+
+    issue_due_installments(env, schedule_id, schedule)?;
+    if schedule.status != ScheduleStatus::Active {
+        // The last installment was just issued; nothing is left to refund
+        return Ok(0);
+    }
+
+    let refunded_installments = schedule.count - schedule.gift_ids.len();
+    let refund_amount = schedule
+        .amount
+        .checked_mul(refunded_installments as i128)
+        .ok_or(Error::InvalidGiftTerms)?;
+
+    let usdc_address = storage::get_usdc_address(env).ok_or(Error::InvalidTokenAddress)?;
+    token::transfer(env, &usdc_address, &schedule.sender, refund_amount)?;
+
+    let total_held = storage::get_total_held(env) - refund_amount;
+    storage::set_total_held(env, total_held);
+
+    schedule.status = ScheduleStatus::Cancelled;
+
+    env.events().publish(
+        (symbol_short!("sched_cnl"),),
+        GiftScheduleCancelled {
+            schedule_id,
+            sender: schedule.sender.clone(),
+            refunded_installments,
+            refund_amount,
+        },
+    );
+
+    Ok(refund_amount)
+}
+
+/// Every installment of a schedule with its current status
+pub fn installments(
+    env: &Env,
+    schedule: &GiftSchedule,
+) -> Result<Vec<ScheduleInstallment>, Error> {
+    let mut installments = Vec::new(env);
+    for index in 0..schedule.count {
+        let gift_id = schedule.gift_ids.get(index);
+        let status = match gift_id.and_then(|gift_id| storage::get_gift(env, gift_id)) {
+            Some(gift) => InstallmentStatus::Issued(gift.status),
+            None if schedule.status == ScheduleStatus::Cancelled => InstallmentStatus::Cancelled,
+            None => InstallmentStatus::Scheduled,
+        };
+
+        installments.push_back(ScheduleInstallment {
+            index,
+            unlock_timestamp: installment_unlock(schedule, index)?,
+            gift_id,
+            status,
+        });
+    }
+    Ok(installments)
+}
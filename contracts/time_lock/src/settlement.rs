@@ -0,0 +1,279 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::events::{
+    PathPaymentExecuted, SettlementPayoutHeld, SettlementPayoutReleased, SettlementUnwound,
+};
+use crate::insurance;
+use crate::oracle;
+use crate::path_payment::{self, PathDiscovery};
+use crate::slippage;
+use crate::storage;
+use crate::token;
+use crate::types::{GiftStatus, Settlement};
+use crate::withdrawal;
+use soroban_sdk::{symbol_short, Address, Env, String};
+
+/// Check that a bank withdrawal can be routed before its USDC is escrowed.
+///
+/// Returns the discovered swap path when a payout token is registered for the
+/// currency, or `None` when the anchor takes USDC directly.
+pub fn quote_payout(
+    env: &Env,
+    usdc_address: &Address,
+    currency: &String,
+    amount_usdc: i128,
+    expected_out: i128,
+    min_amount_out: i128,
+) -> Result<Option<PathDiscovery>, Error> {
+    match storage::get_payout_token(env, currency) {
+        Some(payout_token) => {
+            let router = storage::get_dex_router(env).ok_or(Error::RouterNotConfigured)?;
+            let discovery = path_payment::discover_optimal_path(
+                env,
+                &router,
+                usdc_address,
+                &payout_token,
+                amount_usdc,
+            )?;
+            if discovery.best.amount_out < min_amount_out {
+                return Err(Error::SlippageExceeded);
+            }
+            Ok(Some(discovery))
+        }
+        None => {
+            if expected_out < min_amount_out {
+                return Err(Error::SlippageExceeded);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Convert a bank withdrawal's USDC into what its anchor will be paid, so the
+/// payout is fixed when the withdrawal starts. Swaps along the discovered path
+/// when the currency has a payout token; otherwise the anchor takes USDC.
+///
+/// Returns the asset and amount to escrow for the anchor.
+pub fn escrow_payout(
+    env: &Env,
+    usdc_address: &Address,
+    currency: &String,
+    amount_usdc: i128,
+    min_amount_out: i128,
+    discovery: Option<PathDiscovery>,
+) -> Result<(Address, i128), Error> {
+    // Anchor takes USDC and converts off-chain
+    let Some(discovery) = discovery else {
+        return Ok((usdc_address.clone(), amount_usdc));
+    };
+
+    // Swap along the best path, output lands in the contract
+    let router = storage::get_dex_router(env).ok_or(Error::RouterNotConfigured)?;
+    let payout_token = discovery.best.path.last().ok_or(Error::InvalidSwapPath)?;
+    let swap_result = path_payment::execute_path_payment(
+        env,
+        &router,
+        amount_usdc,
+        min_amount_out,
+        &discovery.best,
+        env.ledger().timestamp() + constants::SWAP_DEADLINE_WINDOW,
+    )?;
+
+    env.events().publish(
+        (symbol_short!("path_exec"),),
+        PathPaymentExecuted {
+            usdc_sent: swap_result.amount_in,
+            amount_out: swap_result.amount_out,
+            currency: currency.clone(),
+            exchange_rate: (swap_result.amount_out * 1_000_000) / swap_result.amount_in,
+            path: discovery.best.path,
+            quoted_amount_out: discovery.best.amount_out,
+            alternatives: discovery.alternatives,
+        },
+    );
+
+    Ok((payout_token, swap_result.amount_out))
+}
+
+/// USDC of a settlement converted into its payout, i.e. less the anchor fee
+pub fn payout_usdc(settlement: &Settlement) -> i128 {
+    settlement.amount_usdc - settlement.anchor_fee
+}
+
+/// Deliver a settlement's escrowed payout and its USDC fee to its anchor
+pub fn pay_anchor(env: &Env, settlement: &Settlement) -> Result<(), Error> {
+    token::transfer(
+        env,
+        &settlement.payout_asset,
+        &settlement.anchor_address,
+        settlement.payout_amount,
+    )?;
+    pay_anchor_fee(env, settlement, &settlement.anchor_address)
+}
+
+/// Send a settlement's unswapped anchor fee to `to`
+fn pay_anchor_fee(env: &Env, settlement: &Settlement, to: &Address) -> Result<(), Error> {
+    if settlement.anchor_fee == 0 {
+        return Ok(());
+    }
+    let usdc_address = storage::get_usdc_address(env).ok_or(Error::InvalidTokenAddress)?;
+    token::transfer(env, &usdc_address, to, settlement.anchor_fee)
+}
+
+/// Release a failed or expired settlement, or hold its escrow when a swapped
+/// payout cannot be swapped back yet. Held settlements can no longer be
+/// confirmed; they wait for `retry_release` or the recipient to accept the
+/// payout token. Returns whether the settlement was released.
+pub fn release_or_hold(env: &Env, gift_id: u64, settlement: &Settlement) -> Result<bool, Error> {
+    if release(env, gift_id, settlement).is_ok() {
+        return Ok(true);
+    }
+
+    let mut held = settlement.clone();
+    held.held = true;
+    storage::set_settlement(env, gift_id, &held);
+
+    env.events().publish(
+        (symbol_short!("settle_h"),),
+        SettlementPayoutHeld {
+            gift_id,
+            asset: settlement.payout_asset.clone(),
+            amount: settlement.payout_amount,
+        },
+    );
+
+    Ok(false)
+}
+
+/// Release a pending settlement's escrow and return the gift to `Unlocked`
+/// so the recipient can retry or withdraw the USDC instead.
+///
+/// A swapped payout, insurance top-up included, is swapped back to USDC first.
+/// The gift keeps its amount: the reserve gets its top-up back and takes any
+/// gain or loss of the round trip, with the fee pool covering a loss the
+/// reserve cannot. The swap is refused if together they could not.
+pub fn release(env: &Env, gift_id: u64, settlement: &Settlement) -> Result<(), Error> {
+    let mut gift = storage::get_gift(env, gift_id).ok_or(Error::GiftNotFound)?;
+
+    let usdc_address = storage::get_usdc_address(env).ok_or(Error::InvalidTokenAddress)?;
+    if settlement.payout_asset != usdc_address {
+        let recovered = unwind_payout(env, &usdc_address, settlement)?;
+        insurance::release_top_up(env, settlement);
+        insurance::absorb_unwind(
+            env,
+            recovered - payout_usdc(settlement) - settlement.top_up_usdc,
+        );
+
+        env.events().publish(
+            (symbol_short!("settle_uw"),),
+            SettlementUnwound {
+                gift_id,
+                asset: settlement.payout_asset.clone(),
+                amount: settlement.payout_amount,
+                usdc_received: recovered,
+            },
+        );
+    }
+
+    clear_escrow(env, gift_id, settlement);
+
+    gift.status = GiftStatus::Unlocked;
+    storage::set_gift(env, gift_id, &gift);
+
+    Ok(())
+}
+
+/// Pay a held settlement's payout token to the gift's recipient, who accepted
+/// it instead of USDC, along with the anchor fee no anchor earned. The
+/// withdrawal completes as if the anchor had confirmed it: the fee is
+/// collected, the top-up debited, and the gift only returns to `Unlocked` if
+/// USDC is left.
+pub fn pay_recipient(env: &Env, gift_id: u64, settlement: &Settlement) -> Result<(), Error> {
+    let mut gift = storage::get_gift(env, gift_id).ok_or(Error::GiftNotFound)?;
+    let recipient = gift.recipient.clone().ok_or(Error::Unauthorized)?;
+    token::transfer(
+        env,
+        &settlement.payout_asset,
+        &recipient,
+        settlement.payout_amount,
+    )?;
+    pay_anchor_fee(env, settlement, &recipient)?;
+    insurance::settle_top_up(env, gift_id, settlement);
+    clear_escrow(env, gift_id, settlement);
+
+    let withdrawn = settlement.amount_usdc + settlement.fee_amount;
+    gift.withdrawn_amount += withdrawn;
+    let remaining_amount = withdrawal::remaining_amount(&gift);
+    gift.status = if remaining_amount == 0 {
+        GiftStatus::Withdrawn
+    } else {
+        GiftStatus::Unlocked
+    };
+    storage::set_gift(env, gift_id, &gift);
+
+    insurance::collect_fee(env, gift_id, settlement.fee_amount);
+    let total_held = storage::get_total_held(env) - withdrawn;
+    storage::set_total_held(env, total_held);
+
+    env.events().publish(
+        (symbol_short!("settle_rp"),),
+        SettlementPayoutReleased {
+            gift_id,
+            recipient,
+            asset: settlement.payout_asset.clone(),
+            amount: settlement.payout_amount,
+            remaining_amount,
+        },
+    );
+
+    Ok(())
+}
+
+/// Drop a settlement and its USDC from its anchor's escrow
+fn clear_escrow(env: &Env, gift_id: u64, settlement: &Settlement) {
+    let escrow =
+        storage::get_anchor_escrow(env, &settlement.anchor_address) - settlement.amount_usdc;
+    storage::set_anchor_escrow(env, &settlement.anchor_address, escrow);
+    storage::remove_settlement(env, gift_id);
+}
+
+/// Swap a settlement's escrowed payout token back to USDC, requiring the
+/// settlement's own rate less the pair's slippage bound, and no more loss than
+/// the reserve and fee pool can cover. Returns the USDC received.
+fn unwind_payout(
+    env: &Env,
+    usdc_address: &Address,
+    settlement: &Settlement,
+) -> Result<i128, Error> {
+    if settlement.expected_amount_out <= 0 || settlement.payout_amount <= 0 {
+        return Err(Error::InvalidExchangeRate);
+    }
+
+    let router = storage::get_dex_router(env).ok_or(Error::RouterNotConfigured)?;
+    let discovery = path_payment::discover_optimal_path(
+        env,
+        &router,
+        &settlement.payout_asset,
+        usdc_address,
+        settlement.payout_amount,
+    )?;
+
+    let currency_pair = oracle::currency_pair(env, &settlement.currency)?;
+    let slippage_config = slippage::config_for_pair(env, &currency_pair)?;
+    let expected_usdc =
+        (settlement.payout_amount * payout_usdc(settlement)) / settlement.expected_amount_out;
+    let owed_usdc = payout_usdc(settlement) + settlement.top_up_usdc;
+    let min_usdc = withdrawal::min_amount_out(&slippage_config, expected_usdc, None)
+        .max(owed_usdc - insurance::loss_capacity(env, settlement));
+
+    let swap_result = path_payment::execute_path_payment(
+        env,
+        &router,
+        settlement.payout_amount,
+        min_usdc,
+        &discovery.best,
+        env.ledger().timestamp() + constants::SWAP_DEADLINE_WINDOW,
+    )?;
+
+    Ok(swap_result.amount_out)
+}
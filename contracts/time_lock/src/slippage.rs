@@ -1,6 +1,7 @@
 use crate::errors::Error;
 use soroban_sdk::contracttype;
-use soroban_sdk::{symbol_short, Address, Env};
+use crate::storage;
+use soroban_sdk::{symbol_short, Address, Env, String};
 
 /// Slippage configuration
 #[contracttype]
@@ -18,6 +19,16 @@ pub fn default_slippage_config(admin: Address) -> SlippageConfig {
     }
 }
 
+/// Slippage configuration for a currency pair, with the pair's own tolerance
+/// replacing the global one when the admin has set it
+pub fn config_for_pair(env: &Env, currency_pair: &String) -> Result<SlippageConfig, Error> {
+    let mut config = storage::get_slippage_config(env).ok_or(Error::Unauthorized)?;
+    if let Some(slippage_bps) = storage::get_pair_slippage(env, currency_pair) {
+        config.max_slippage_bps = slippage_bps;
+    }
+    Ok(config)
+}
+
 /// Validate slippage bounds
 pub fn validate_slippage_bounds(slippage_bps: u32) -> Result<(), Error> {
     if slippage_bps > 10000 {
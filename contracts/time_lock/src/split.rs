@@ -0,0 +1,128 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::events::GiftCreated;
+use crate::storage;
+use crate::types::{Gift, GiftKind, GiftStatus};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Symbol, Vec};
+
+/// One recipient's part of a split gift
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitShare {
+    pub recipient_phone_hash: BytesN<32>,
+    pub share_bps: u32, // Share of the gift in basis points; all shares sum to 10000
+}
+
+/// Gift escrowed once and paid out to several recipients, each through their own share gift
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitGift {
+    pub sender: Address,
+    pub amount: i128,
+    pub claim_deadline: u64, // Last time shares can be claimed; the rest can then be refunded
+    pub gift_ids: Vec<u64>,  // Share gifts, in the order of the shares
+}
+
+/// Validate the shares of a new split gift and return the USDC of each
+pub fn share_amounts(
+    env: &Env,
+    amount: i128,
+    shares: &Vec<SplitShare>,
+) -> Result<Vec<i128>, Error> {
+    if shares.is_empty() || shares.len() > constants::MAX_SPLIT_RECIPIENTS {
+        return Err(Error::InvalidGiftTerms);
+    }
+
+    let mut total_bps: u32 = 0;
+    for share in shares.iter() {
+        if share.share_bps == 0 {
+            return Err(Error::InvalidGiftTerms);
+        }
+        total_bps = total_bps.saturating_add(share.share_bps);
+    }
+    if total_bps != 10000 {
+        return Err(Error::InvalidGiftTerms);
+    }
+
+    // The last share takes the rounding remainder so the shares add up to the gift
+    let mut amounts = Vec::new(env);
+    let mut allocated = 0;
+    for (index, share) in shares.iter().enumerate() {
+        let share_amount = if index as u32 == shares.len() - 1 {
+            amount - allocated
+        } else {
+            (amount * share.share_bps as i128) / 10000
+        };
+        if share_amount < constants::MIN_GIFT_AMOUNT {
+            return Err(Error::InvalidAmount);
+        }
+        allocated += share_amount;
+        amounts.push_back(share_amount);
+    }
+
+    Ok(amounts)
+}
+
+/// Create one gift per share, funded from the split gift's escrow
+pub fn issue_share_gifts(
+    env: &Env,
+    split_id: u64,
+    sender: &Address,
+    unlock_timestamp: u64,
+    shares: &Vec<SplitShare>,
+    amounts: &Vec<i128>,
+) -> Vec<u64> {
+    let mut gift_ids = Vec::new(env);
+    for (share, amount) in shares.iter().zip(amounts.iter()) {
+        let gift_id = storage::increment_next_gift_id(env);
+        let gift = Gift {
+            sender: sender.clone(),
+            recipient: None,
+            amount,
+            withdrawn_amount: 0,
+            unlock_timestamp,
+            recipient_phone_hash: share.recipient_phone_hash.clone(),
+            status: GiftStatus::Created,
+            kind: GiftKind::Standard,
+        };
+        storage::set_gift(env, gift_id, &gift);
+        storage::set_share_split(env, gift_id, split_id);
+
+        env.events().publish(
+            (Symbol::new(env, "gift_created"),),
+            GiftCreated {
+                gift_id,
+                sender: sender.clone(),
+                amount,
+                unlock_time: unlock_timestamp,
+                recipient_hash: share.recipient_phone_hash,
+            },
+        );
+
+        gift_ids.push_back(gift_id);
+    }
+    gift_ids
+}
+
+/// Fail if `gift_id` is a share of a split gift whose claim deadline has passed
+pub fn ensure_claimable(env: &Env, gift_id: u64) -> Result<(), Error> {
+    if let Some(split_id) = storage::get_share_split(env, gift_id) {
+        let split = storage::get_split_gift(env, split_id).ok_or(Error::GiftNotFound)?;
+        if env.ledger().timestamp() > split.claim_deadline {
+            return Err(Error::DeadlineExceeded);
+        }
+    }
+    Ok(())
+}
+
+/// Whether every share has been withdrawn, refunded or rejected
+pub fn is_settled(env: &Env, split: &SplitGift) -> bool {
+    split.gift_ids.iter().all(|gift_id| {
+        storage::get_gift(env, gift_id).is_some_and(|gift| {
+            matches!(
+                gift.status,
+                GiftStatus::Withdrawn | GiftStatus::Refunded | GiftStatus::Rejected
+            )
+        })
+    })
+}
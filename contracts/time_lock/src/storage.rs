@@ -1,11 +1,21 @@
+use crate::anchor::AnchorConfig;
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::group::GroupGift;
+use crate::insurance::{self, InsuranceConfig};
 use crate::oracle::OracleConfig;
+use crate::red_envelope::RedEnvelope;
+use crate::schedule::GiftSchedule;
 use crate::slippage::SlippageConfig;
-use crate::types::{Gift, PriceCache};
-use soroban_sdk::{contracttype, Address, BytesN, Env, String};
+use crate::split::SplitGift;
+use crate::types::{Gift, PriceCache, Settlement};
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Vec};
 
 const DAY_IN_LEDGERS: u32 = 17280;
 const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
 const INSTANCE_LIFETIME_THRESHOLD: u32 = DAY_IN_LEDGERS;
+const LEDGER_SECONDS: u64 = 5;
+const USED_TX_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+const USED_TX_LIFETIME_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
 
 #[contracttype]
 #[derive(Clone)]
@@ -16,12 +26,41 @@ pub enum DataKey {
     SlippageConfig,
     NextGiftId,
     Gift(u64),
-    PriceCache,
+    PriceCache(String),
     UsdcAddress,
     TotalHeld,
     TotalGifted,
     TotalFees,
     PaymentReference(String),
+    CircuitBreakerConfig(String),
+    CircuitBreakerTripped(String),
+    DexRouter,
+    PayoutToken(String),
+    IntermediateAssets,
+    Settlement(u64),
+    AnchorEscrow(Address),
+    Anchor(Address),
+    AnchorList,
+    UsedQuote(String),
+    PairSlippage(String),
+    InsuranceConfig,
+    InsuranceReserve,
+    Relayer(Address),
+    MaxRelayFee,
+    NextScheduleId,
+    Schedule(u64),
+    GroupGift(u64),
+    Contribution(u64, Address),
+    NextSplitId,
+    SplitGift(u64),
+    NextEnvelopeId,
+    RedEnvelope(u64),
+    RecipientChanges(u64),
+    NextSettlementNonce,
+    UsedAnchorTx(String),
+    ShareSplit(u64),
+    ReferencePrice(String),
+    InsuranceCommitted,
 }
 
 pub fn extend_instance_ttl(env: &Env) {
@@ -75,6 +114,19 @@ pub fn set_slippage_config(env: &Env, config: &SlippageConfig) {
     extend_instance_ttl(env);
 }
 
+pub fn get_pair_slippage(env: &Env, currency_pair: &String) -> Option<u32> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PairSlippage(currency_pair.clone()))
+}
+
+pub fn set_pair_slippage(env: &Env, currency_pair: &String, slippage_bps: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::PairSlippage(currency_pair.clone()), &slippage_bps);
+    extend_instance_ttl(env);
+}
+
 pub fn get_next_gift_id(env: &Env) -> u64 {
     env.storage().instance().get(&DataKey::NextGiftId).unwrap_or(1)
 }
@@ -95,16 +147,255 @@ pub fn set_gift(env: &Env, id: u64, gift: &Gift) {
     extend_instance_ttl(env);
 }
 
+pub fn increment_next_schedule_id(env: &Env) -> u64 {
+    let id: u64 = env.storage().instance().get(&DataKey::NextScheduleId).unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextScheduleId, &(id + 1));
+    extend_instance_ttl(env);
+    id
+}
+
+pub fn get_schedule(env: &Env, id: u64) -> Option<GiftSchedule> {
+    env.storage().instance().get(&DataKey::Schedule(id))
+}
+
+pub fn set_schedule(env: &Env, id: u64, schedule: &GiftSchedule) {
+    env.storage().instance().set(&DataKey::Schedule(id), schedule);
+    extend_instance_ttl(env);
+}
+
+pub fn increment_next_split_id(env: &Env) -> u64 {
+    let id: u64 = env.storage().instance().get(&DataKey::NextSplitId).unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextSplitId, &(id + 1));
+    extend_instance_ttl(env);
+    id
+}
+
+pub fn get_split_gift(env: &Env, id: u64) -> Option<SplitGift> {
+    env.storage().instance().get(&DataKey::SplitGift(id))
+}
+
+pub fn set_split_gift(env: &Env, id: u64, split: &SplitGift) {
+    env.storage().instance().set(&DataKey::SplitGift(id), split);
+    extend_instance_ttl(env);
+}
+
+pub fn get_share_split(env: &Env, gift_id: u64) -> Option<u64> {
+    env.storage().instance().get(&DataKey::ShareSplit(gift_id))
+}
+
+pub fn set_share_split(env: &Env, gift_id: u64, split_id: u64) {
+    env.storage().instance().set(&DataKey::ShareSplit(gift_id), &split_id);
+    extend_instance_ttl(env);
+}
+
+pub fn increment_next_envelope_id(env: &Env) -> u64 {
+    let id: u64 = env.storage().instance().get(&DataKey::NextEnvelopeId).unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextEnvelopeId, &(id + 1));
+    extend_instance_ttl(env);
+    id
+}
+
+pub fn get_red_envelope(env: &Env, id: u64) -> Option<RedEnvelope> {
+    env.storage().instance().get(&DataKey::RedEnvelope(id))
+}
+
+pub fn set_red_envelope(env: &Env, id: u64, envelope: &RedEnvelope) {
+    env.storage().instance().set(&DataKey::RedEnvelope(id), envelope);
+    extend_instance_ttl(env);
+}
+
+pub fn get_group_gift(env: &Env, gift_id: u64) -> Option<GroupGift> {
+    env.storage().instance().get(&DataKey::GroupGift(gift_id))
+}
+
+pub fn set_group_gift(env: &Env, gift_id: u64, group: &GroupGift) {
+    env.storage().instance().set(&DataKey::GroupGift(gift_id), group);
+    extend_instance_ttl(env);
+}
+
+pub fn get_contribution(env: &Env, gift_id: u64, contributor: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Contribution(gift_id, contributor.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_contribution(env: &Env, gift_id: u64, contributor: &Address, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::Contribution(gift_id, contributor.clone()), &amount);
+    extend_instance_ttl(env);
+}
+
+pub fn get_recipient_changes(env: &Env, gift_id: u64) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RecipientChanges(gift_id))
+        .unwrap_or(0)
+}
+
+pub fn set_recipient_changes(env: &Env, gift_id: u64, changes: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::RecipientChanges(gift_id), &changes);
+    extend_instance_ttl(env);
+}
+
+// Pending bank settlements and USDC escrowed per anchor
+pub fn get_settlement(env: &Env, gift_id: u64) -> Option<Settlement> {
+    env.storage().instance().get(&DataKey::Settlement(gift_id))
+}
+
+pub fn set_settlement(env: &Env, gift_id: u64, settlement: &Settlement) {
+    env.storage()
+        .instance()
+        .set(&DataKey::Settlement(gift_id), settlement);
+    extend_instance_ttl(env);
+}
+
+pub fn remove_settlement(env: &Env, gift_id: u64) {
+    env.storage().instance().remove(&DataKey::Settlement(gift_id));
+}
+
+pub fn increment_next_settlement_nonce(env: &Env) -> u64 {
+    let nonce: u64 = env.storage().instance().get(&DataKey::NextSettlementNonce).unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextSettlementNonce, &(nonce + 1));
+    extend_instance_ttl(env);
+    nonce
+}
+
+pub fn get_anchor_escrow(env: &Env, anchor: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::AnchorEscrow(anchor.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_anchor_escrow(env: &Env, anchor: &Address, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::AnchorEscrow(anchor.clone()), &amount);
+    extend_instance_ttl(env);
+}
+
+// Anchor registry
+pub fn get_anchor(env: &Env, anchor: &Address) -> Option<AnchorConfig> {
+    env.storage().instance().get(&DataKey::Anchor(anchor.clone()))
+}
+
+pub fn set_anchor(env: &Env, anchor: &Address, config: &AnchorConfig) {
+    env.storage()
+        .instance()
+        .set(&DataKey::Anchor(anchor.clone()), config);
+    extend_instance_ttl(env);
+}
+
+pub fn get_anchor_list(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::AnchorList)
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_anchor_list(env: &Env, anchors: &Vec<Address>) {
+    env.storage().instance().set(&DataKey::AnchorList, anchors);
+    extend_instance_ttl(env);
+}
+
+pub fn is_quote_used(env: &Env, quote_id: &String) -> bool {
+    env.storage()
+        .temporary()
+        .has(&DataKey::UsedQuote(quote_id.clone()))
+}
+
+/// Mark a quote ID used until a day past `expires_at`, after which the quote
+/// is rejected as expired anyway
+pub fn set_quote_used(env: &Env, quote_id: &String, expires_at: u64) {
+    let key = DataKey::UsedQuote(quote_id.clone());
+    let remaining = expires_at.saturating_sub(env.ledger().timestamp()) / LEDGER_SECONDS;
+    let ttl = (remaining.min(u32::MAX as u64) as u32)
+        .saturating_add(DAY_IN_LEDGERS)
+        .min(env.storage().max_ttl());
+    env.storage().temporary().set(&key, &true);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn is_anchor_tx_used(env: &Env, anchor_tx_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::UsedAnchorTx(anchor_tx_id.clone()))
+}
+
+/// Mark an anchor transaction ID used. Its entry is persistent, so once its
+/// TTL runs out it is archived rather than forgotten and still cannot be reused
+pub fn set_anchor_tx_used(env: &Env, anchor_tx_id: &String) {
+    let key = DataKey::UsedAnchorTx(anchor_tx_id.clone());
+    env.storage().persistent().set(&key, &true);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, USED_TX_LIFETIME_THRESHOLD, USED_TX_BUMP_AMOUNT);
+}
+
+pub fn get_price_cache(env: &Env, currency_pair: &String) -> Option<PriceCache> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PriceCache(currency_pair.clone()))
+}
+
+pub fn set_price_cache(env: &Env, currency_pair: &String, cache: &PriceCache) {
+    env.storage()
+        .instance()
+        .set(&DataKey::PriceCache(currency_pair.clone()), cache);
+    extend_instance_ttl(env);
+}
+
+// Circuit breaker per currency pair
+pub fn get_circuit_breaker_config(env: &Env, currency_pair: &String) -> Option<CircuitBreakerConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKey::CircuitBreakerConfig(currency_pair.clone()))
+}
 
-pub fn get_price_cache(env: &Env) -> Option<PriceCache> {
-    env.storage().instance().get(&DataKey::PriceCache)
+pub fn set_circuit_breaker_config(env: &Env, currency_pair: &String, config: &CircuitBreakerConfig) {
+    env.storage()
+        .instance()
+        .set(&DataKey::CircuitBreakerConfig(currency_pair.clone()), config);
+    extend_instance_ttl(env);
 }
 
-pub fn set_price_cache(env: &Env, cache: &PriceCache) {
-    env.storage().instance().set(&DataKey::PriceCache, cache);
+pub fn is_circuit_breaker_tripped(env: &Env, currency_pair: &String) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::CircuitBreakerTripped(currency_pair.clone()))
+        .unwrap_or(false)
+}
+
+pub fn set_circuit_breaker_tripped(env: &Env, currency_pair: &String, tripped: bool) {
+    env.storage()
+        .instance()
+        .set(&DataKey::CircuitBreakerTripped(currency_pair.clone()), &tripped);
     extend_instance_ttl(env);
 }
 
+pub fn get_reference_price(env: &Env, currency_pair: &String) -> Option<PriceCache> {
+    env.storage()
+        .instance()
+        .get(&DataKey::ReferencePrice(currency_pair.clone()))
+}
+
+pub fn set_reference_price(env: &Env, currency_pair: &String, reference: &PriceCache) {
+    env.storage()
+        .instance()
+        .set(&DataKey::ReferencePrice(currency_pair.clone()), reference);
+    extend_instance_ttl(env);
+}
+
+pub fn remove_reference_price(env: &Env, currency_pair: &String) {
+    env.storage()
+        .instance()
+        .remove(&DataKey::ReferencePrice(currency_pair.clone()));
+}
+
 // USDC Token address
 pub fn get_usdc_address(env: &Env) -> Option<Address> {
     env.storage().instance().get(&DataKey::UsdcAddress)
@@ -115,6 +406,43 @@ pub fn set_usdc_address(env: &Env, address: &Address) {
     extend_instance_ttl(env);
 }
 
+// DEX router and payout token used for bank withdrawals
+pub fn get_dex_router(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::DexRouter)
+}
+
+pub fn set_dex_router(env: &Env, router: &Address) {
+    env.storage().instance().set(&DataKey::DexRouter, router);
+    extend_instance_ttl(env);
+}
+
+pub fn get_payout_token(env: &Env, currency: &String) -> Option<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PayoutToken(currency.clone()))
+}
+
+pub fn set_payout_token(env: &Env, currency: &String, address: &Address) {
+    env.storage()
+        .instance()
+        .set(&DataKey::PayoutToken(currency.clone()), address);
+    extend_instance_ttl(env);
+}
+
+pub fn get_intermediate_assets(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::IntermediateAssets)
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_intermediate_assets(env: &Env, assets: &Vec<Address>) {
+    env.storage()
+        .instance()
+        .set(&DataKey::IntermediateAssets, assets);
+    extend_instance_ttl(env);
+}
+
 // Internal Balance Tracking
 pub fn get_total_held(env: &Env) -> i128 {
     env.storage().instance().get(&DataKey::TotalHeld).unwrap_or(0)
@@ -143,6 +471,73 @@ pub fn set_total_fees(env: &Env, amount: i128) {
     extend_instance_ttl(env);
 }
 
+pub fn get_insurance_config(env: &Env) -> InsuranceConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::InsuranceConfig)
+        .unwrap_or_else(insurance::default_insurance_config)
+}
+
+pub fn set_insurance_config(env: &Env, config: &InsuranceConfig) {
+    env.storage()
+        .instance()
+        .set(&DataKey::InsuranceConfig, config);
+    extend_instance_ttl(env);
+}
+
+pub fn get_insurance_reserve(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::InsuranceReserve)
+        .unwrap_or(0)
+}
+
+pub fn set_insurance_reserve(env: &Env, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::InsuranceReserve, &amount);
+    extend_instance_ttl(env);
+}
+
+pub fn get_insurance_committed(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::InsuranceCommitted)
+        .unwrap_or(0)
+}
+
+pub fn set_insurance_committed(env: &Env, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::InsuranceCommitted, &amount);
+    extend_instance_ttl(env);
+}
+
+pub fn is_relayer_allowed(env: &Env, relayer: &Address) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::Relayer(relayer.clone()))
+        .unwrap_or(false)
+}
+
+pub fn set_relayer_allowed(env: &Env, relayer: &Address, allowed: bool) {
+    env.storage()
+        .instance()
+        .set(&DataKey::Relayer(relayer.clone()), &allowed);
+    extend_instance_ttl(env);
+}
+
+pub fn get_max_relay_fee(env: &Env) -> i128 {
+    env.storage().instance().get(&DataKey::MaxRelayFee).unwrap_or(0)
+}
+
+pub fn set_max_relay_fee(env: &Env, max_relay_fee: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::MaxRelayFee, &max_relay_fee);
+    extend_instance_ttl(env);
+}
+
 // Payment Reference tracking
 pub fn get_payment_reference_gift_id(env: &Env, payment_ref: &String) -> Option<u64> {
     env.storage()
@@ -2,7 +2,10 @@
 
 mod tests {
     use crate::*;
-    use soroban_sdk::{testutils::Address as TestAddress, Address, Env, String};
+    use soroban_sdk::{
+        testutils::{Address as TestAddress, Ledger},
+        Address, Env, String,
+    };
 
     fn setup_test_env() -> (Env, Address, Address, Address) {
         let env = Env::default();
@@ -20,6 +23,8 @@ mod tests {
         let oracle_config = oracle::OracleConfig {
             oracle_address: oracle.clone(),
             max_oracle_age: 300,
+            max_oracle_age_ledgers: 60,
+            staleness_mode: oracle::StalenessMode::Seconds,
             is_paused: false,
         };
         storage::set_admin(env, admin);
@@ -90,6 +95,8 @@ mod tests {
             let oracle_config = oracle::OracleConfig {
                 oracle_address: fake_oracle,
                 max_oracle_age: 300,
+                max_oracle_age_ledgers: 60,
+                staleness_mode: oracle::StalenessMode::Seconds,
                 is_paused: false,
             };
             storage::set_oracle_config(&env, &oracle_config);
@@ -420,6 +427,151 @@ mod tests {
         assert!(oracle::validate_rate_bounds(-1000000).is_err());
     }
 
+    #[test]
+    fn test_validate_data_freshness_modes() {
+        let env = Env::default();
+        let mut config = oracle::default_oracle_config(<Address as TestAddress>::generate(&env));
+        config.max_oracle_age = 300;
+        config.max_oracle_age_ledgers = 60;
+        let data = types::PriceCache {
+            rate: 1_000_000,
+            timestamp: 1000,
+            ledger: 100,
+        };
+
+        // Old by time, recent by ledger
+        config.staleness_mode = oracle::StalenessMode::Seconds;
+        assert_eq!(
+            oracle::validate_data_freshness(&config, 1301, 110, &data),
+            Err(errors::Error::StaleOracleDataByTime)
+        );
+        config.staleness_mode = oracle::StalenessMode::Ledgers;
+        assert!(oracle::validate_data_freshness(&config, 1301, 110, &data).is_ok());
+
+        // Recent by time, old by ledger
+        assert_eq!(
+            oracle::validate_data_freshness(&config, 1010, 161, &data),
+            Err(errors::Error::StaleOracleDataByLedger)
+        );
+        config.staleness_mode = oracle::StalenessMode::Seconds;
+        assert!(oracle::validate_data_freshness(&config, 1010, 161, &data).is_ok());
+
+        // Both limits apply
+        config.staleness_mode = oracle::StalenessMode::Both;
+        assert!(oracle::validate_data_freshness(&config, 1300, 160, &data).is_ok());
+        assert_eq!(
+            oracle::validate_data_freshness(&config, 1301, 160, &data),
+            Err(errors::Error::StaleOracleDataByTime)
+        );
+        assert_eq!(
+            oracle::validate_data_freshness(&config, 1300, 161, &data),
+            Err(errors::Error::StaleOracleDataByLedger)
+        );
+    }
+
+    #[test]
+    fn test_validate_staleness_policy() {
+        use oracle::StalenessMode;
+
+        assert!(oracle::validate_staleness_policy(StalenessMode::Seconds, 300, 0).is_ok());
+        assert!(oracle::validate_staleness_policy(StalenessMode::Ledgers, 0, 60).is_ok());
+        assert!(oracle::validate_staleness_policy(StalenessMode::Both, 300, 60).is_ok());
+        assert_eq!(
+            oracle::validate_staleness_policy(StalenessMode::Seconds, 0, 60),
+            Err(errors::Error::InvalidStalenessPolicy)
+        );
+        assert_eq!(
+            oracle::validate_staleness_policy(StalenessMode::Ledgers, 300, 0),
+            Err(errors::Error::InvalidStalenessPolicy)
+        );
+        assert_eq!(
+            oracle::validate_staleness_policy(StalenessMode::Both, 300, 0),
+            Err(errors::Error::InvalidStalenessPolicy)
+        );
+        assert_eq!(
+            oracle::validate_staleness_policy(StalenessMode::Both, 0, 60),
+            Err(errors::Error::InvalidStalenessPolicy)
+        );
+    }
+
+    #[test]
+    fn test_set_max_oracle_age_keeps_policy_valid() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+        });
+
+        // The time limit cannot be dropped while the policy checks it
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                TimeLockContract::set_max_oracle_age(env.clone(), 0),
+                Err(errors::Error::InvalidStalenessPolicy)
+            );
+        });
+        env.as_contract(&contract_id, || {
+            TimeLockContract::set_max_oracle_age(env.clone(), 600).unwrap();
+            assert_eq!(storage::get_oracle_config(&env).unwrap().max_oracle_age, 600);
+        });
+
+        env.as_contract(&contract_id, || {
+            TimeLockContract::set_oracle_staleness_policy(
+                env.clone(),
+                oracle::StalenessMode::Ledgers,
+                600,
+                10,
+            )
+            .unwrap();
+        });
+        env.as_contract(&contract_id, || {
+            TimeLockContract::set_max_oracle_age(env.clone(), 0).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_price_cache_staleness_by_ledger() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+            TimeLockContract::set_oracle_staleness_policy(
+                env.clone(),
+                oracle::StalenessMode::Ledgers,
+                300,
+                10,
+            )
+            .unwrap();
+
+            let pair = String::from_str(&env, "USDC/NGN");
+            env.ledger().set_timestamp(1000);
+            env.ledger().set_sequence_number(100);
+            storage::set_price_cache(
+                &env,
+                &pair,
+                &types::PriceCache {
+                    rate: 1_010_000,
+                    timestamp: 1000,
+                    ledger: 100,
+                },
+            );
+
+            // Far past the time limit but within the ledger limit: cache is served
+            env.ledger().set_timestamp(1000 + 3600);
+            env.ledger().set_sequence_number(110);
+            let config = storage::get_oracle_config(&env).unwrap();
+            assert_eq!(oracle::get_price(&env, &config, &pair), Ok(1_010_000));
+
+            // Past the ledger limit: a new reading replaces the cache
+            env.ledger().set_sequence_number(111);
+            assert_eq!(oracle::get_price(&env, &config, &pair), Ok(1_000_000));
+            let cached = storage::get_price_cache(&env, &pair).unwrap();
+            assert_eq!(cached.timestamp, 1000 + 3600);
+            assert_eq!(cached.ledger, 111);
+        });
+    }
+
     #[test]
     fn test_validate_slippage_bounds() {
         assert!(slippage::validate_slippage_bounds(200).is_ok());
@@ -427,6 +579,34 @@ mod tests {
         assert!(slippage::validate_slippage_bounds(10001).is_err());
     }
 
+    #[test]
+    fn test_router_error_mapping() {
+        use path_payment::RouterError;
+        use soroban_sdk::InvokeError;
+
+        assert_eq!(
+            path_payment::router_error(Ok(RouterError::InsufficientLiquidity)),
+            errors::Error::InsufficientLiquidity
+        );
+        assert_eq!(
+            path_payment::router_error(Ok(RouterError::InsufficientOutputAmount)),
+            errors::Error::SlippageExceeded
+        );
+        assert_eq!(
+            path_payment::router_error(Ok(RouterError::Expired)),
+            errors::Error::DeadlineExceeded
+        );
+        assert_eq!(
+            path_payment::router_error(Ok(RouterError::NoPool)),
+            errors::Error::InvalidSwapPath
+        );
+        assert_eq!(path_payment::router_error(Err(InvokeError::Abort)), errors::Error::SwapFailed);
+        assert_eq!(
+            path_payment::router_error(Err(InvokeError::Contract(99))),
+            errors::Error::SwapFailed
+        );
+    }
+
     #[test]
     fn test_rate_difference_calculations() {
         // Test various rate differences
@@ -436,6 +616,464 @@ mod tests {
         assert_eq!(slippage::calculate_rate_difference(500000, 450000), -1000); // -10%
     }
 
+    #[test]
+    fn test_circuit_breaker_trips_on_large_move() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+
+            let pair = String::from_str(&env, "USDC/NGN");
+            let cb_config = circuit_breaker::CircuitBreakerConfig {
+                max_change_bps: 1000,
+                interval: 3600,
+            };
+            storage::set_circuit_breaker_config(&env, &pair, &cb_config);
+
+            // Previous reading 30% above what the oracle now reports
+            let previous = types::PriceCache {
+                rate: 1_300_000,
+                timestamp: 1000,
+                ledger: 0,
+            };
+            storage::set_price_cache(&env, &pair, &previous);
+            storage::set_reference_price(&env, &pair, &previous);
+            env.ledger().set_timestamp(1000 + 301);
+
+            let config = storage::get_oracle_config(&env).unwrap();
+            let rate = oracle::get_price(&env, &config, &pair).unwrap();
+
+            // Reading is stored but the pair is tripped
+            assert_eq!(rate, 1_000_000);
+            assert_eq!(storage::get_price_cache(&env, &pair).unwrap().rate, 1_000_000);
+            assert!(storage::is_circuit_breaker_tripped(&env, &pair));
+            assert_eq!(
+                circuit_breaker::ensure_not_tripped(&env, &pair),
+                Err(errors::Error::CircuitBreakerTripped)
+            );
+        });
+
+        env.as_contract(&contract_id, || {
+            let pair = String::from_str(&env, "USDC/NGN");
+            TimeLockContract::reset_circuit_breaker(env.clone(), pair.clone()).unwrap();
+            assert_eq!(
+                TimeLockContract::is_circuit_breaker_tripped(env.clone(), pair),
+                Ok(false)
+            );
+        });
+    }
+
+    #[test]
+    fn test_circuit_breaker_stays_tripped_after_interval() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+
+            let pair = String::from_str(&env, "USDC/NGN");
+            TimeLockContract::set_circuit_breaker(env.clone(), pair.clone(), 1000, 600).unwrap();
+
+            let reference = types::PriceCache {
+                rate: 1_300_000,
+                timestamp: 1000,
+                ledger: 0,
+            };
+            storage::set_price_cache(&env, &pair, &reference);
+            storage::set_reference_price(&env, &pair, &reference);
+
+            // A reading after the interval is still compared with the reference
+            env.ledger().set_timestamp(1000 + 601);
+            let config = storage::get_oracle_config(&env).unwrap();
+            assert_eq!(oracle::get_price(&env, &config, &pair), Ok(1_000_000));
+            assert!(storage::is_circuit_breaker_tripped(&env, &pair));
+
+            // Neither time nor further readings clear the trip
+            env.ledger().set_timestamp(1000 + 601 * 3);
+            assert_eq!(
+                TimeLockContract::check_exchange_rate(env.clone(), pair.clone()),
+                Ok(1_000_000)
+            );
+            assert!(storage::is_circuit_breaker_tripped(&env, &pair));
+            assert_eq!(storage::get_reference_price(&env, &pair), Some(reference));
+        });
+
+        // After a reset the next reading becomes the reference
+        env.as_contract(&contract_id, || {
+            let pair = String::from_str(&env, "USDC/NGN");
+            TimeLockContract::reset_circuit_breaker(env.clone(), pair.clone()).unwrap();
+            env.ledger().set_timestamp(1000 + 601 * 5);
+            let config = storage::get_oracle_config(&env).unwrap();
+            assert_eq!(oracle::get_price(&env, &config, &pair), Ok(1_000_000));
+            assert!(!storage::is_circuit_breaker_tripped(&env, &pair));
+            assert_eq!(
+                storage::get_reference_price(&env, &pair).unwrap().rate,
+                1_000_000
+            );
+        });
+    }
+
+    #[test]
+    fn test_circuit_breaker_reference_moves_once_per_interval() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+
+            let pair = String::from_str(&env, "USDC/NGN");
+            TimeLockContract::set_circuit_breaker(env.clone(), pair.clone(), 1000, 600).unwrap();
+        });
+
+        let pair = String::from_str(&env, "USDC/NGN");
+        let check = |rate: i128, timestamp: u64| {
+            let reading = types::PriceCache {
+                rate,
+                timestamp,
+                ledger: 0,
+            };
+            circuit_breaker::check_reading(&env, &pair, &reading)
+        };
+        let reference_rate = || storage::get_reference_price(&env, &pair).unwrap().rate;
+
+        env.as_contract(&contract_id, || {
+            // The first reading becomes the reference
+            assert!(!check(1_000_000, 1000));
+
+            // Small steps within the interval are measured from the same reference
+            assert!(!check(1_060_000, 1200));
+            assert!(check(1_120_000, 1400));
+            assert_eq!(reference_rate(), 1_000_000);
+        });
+
+        // Once the interval has passed an accepted reading moves the reference
+        env.as_contract(&contract_id, || {
+            TimeLockContract::reset_circuit_breaker(env.clone(), pair.clone()).unwrap();
+            assert!(!check(1_000_000, 2000));
+            assert!(!check(1_090_000, 2600));
+            assert!(!check(1_180_000, 3000));
+            assert_eq!(reference_rate(), 1_090_000);
+        });
+    }
+
+    #[test]
+    fn test_withdraw_to_bank_fails_when_circuit_breaker_tripped() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+
+            let recipient = <Address as TestAddress>::generate(&env);
+            let gift = types::Gift {
+                sender: <Address as TestAddress>::generate(&env),
+                recipient: Some(recipient),
+                amount: constants::MIN_GIFT_AMOUNT,
+                withdrawn_amount: 0,
+                unlock_timestamp: 0,
+                recipient_phone_hash: soroban_sdk::BytesN::from_array(&env, &[1u8; 32]),
+                status: types::GiftStatus::Unlocked,
+                kind: types::GiftKind::Standard,
+            };
+            storage::set_gift(&env, 1, &gift);
+
+            let anchor = <Address as TestAddress>::generate(&env);
+            let anchor_config = anchor::AnchorConfig {
+                currencies: soroban_sdk::vec![&env, String::from_str(&env, "NGN")],
+                min_payout: 1,
+                max_payout: constants::MAX_GIFT_AMOUNT,
+                fee_bps: 0,
+                enabled: true,
+                signing_key: soroban_sdk::BytesN::from_array(&env, &[0u8; 32]),
+            };
+            storage::set_anchor(&env, &anchor, &anchor_config);
+
+            let pair = String::from_str(&env, "USDC/NGN");
+            storage::set_circuit_breaker_tripped(&env, &pair, true);
+
+            let payout = withdrawal::BankPayout {
+                recipient_bank_details_hash: String::from_str(&env, "memo"),
+                anchor_address: anchor,
+                currency: String::from_str(&env, "NGN"),
+                min_amount_out: None,
+                deadline: None,
+            };
+            let result = TimeLockContract::withdraw_to_bank(env.clone(), 1, payout, None);
+            assert_eq!(result, Err(errors::Error::CircuitBreakerTripped));
+        });
+    }
+
+    #[test]
+    fn test_validate_circuit_breaker_config() {
+        let valid = circuit_breaker::CircuitBreakerConfig {
+            max_change_bps: 3000,
+            interval: 3600,
+        };
+        assert!(circuit_breaker::validate_circuit_breaker_config(&valid).is_ok());
+
+        let zero_change = circuit_breaker::CircuitBreakerConfig {
+            max_change_bps: 0,
+            interval: 3600,
+        };
+        assert!(circuit_breaker::validate_circuit_breaker_config(&zero_change).is_err());
+
+        let zero_interval = circuit_breaker::CircuitBreakerConfig {
+            max_change_bps: 3000,
+            interval: 0,
+        };
+        assert!(circuit_breaker::validate_circuit_breaker_config(&zero_interval).is_err());
+    }
+
     // Note: Full integration tests with token transfers are in tests/integration.rs
     // Those tests properly mock the USDC token contract for create_gift/withdraw flows
+
+    #[test]
+    fn test_currency_pair_for_payout_currency() {
+        let env = Env::default();
+
+        let pair = oracle::currency_pair(&env, &String::from_str(&env, "KES")).unwrap();
+        assert_eq!(pair, String::from_str(&env, "USDC/KES"));
+
+        assert_eq!(
+            oracle::currency_pair(&env, &String::from_str(&env, "")),
+            Err(errors::Error::CurrencyNotSupported)
+        );
+        assert_eq!(
+            oracle::currency_pair(&env, &String::from_str(&env, "ABCDEFGHIJKLM")),
+            Err(errors::Error::CurrencyNotSupported)
+        );
+    }
+
+    #[test]
+    fn test_used_quote_ids_outlive_their_quotes() {
+        use soroban_sdk::testutils::storage::Temporary;
+
+        let env = Env::default();
+        let contract_id = env.register(TimeLockContract, ());
+        env.ledger().set_timestamp(1_000);
+
+        env.as_contract(&contract_id, || {
+            let quote_id = String::from_str(&env, "q1");
+            assert!(!storage::is_quote_used(&env, &quote_id));
+
+            // An hour-long quote stays marked for its 720 ledgers plus a day
+            storage::set_quote_used(&env, &quote_id, 1_000 + 3_600);
+            assert!(storage::is_quote_used(&env, &quote_id));
+            let key = storage::DataKey::UsedQuote(quote_id);
+            assert_eq!(env.storage().temporary().get_ttl(&key), 720 + 17_280);
+        });
+    }
+
+    #[test]
+    fn test_used_anchor_tx_ids_are_persistent() {
+        use soroban_sdk::testutils::storage::Persistent;
+
+        let env = Env::default();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+            assert!(!storage::is_anchor_tx_used(&env, &anchor_tx_id));
+
+            storage::set_anchor_tx_used(&env, &anchor_tx_id);
+            assert!(storage::is_anchor_tx_used(&env, &anchor_tx_id));
+            let key = storage::DataKey::UsedAnchorTx(anchor_tx_id);
+            assert_eq!(env.storage().persistent().get_ttl(&key), 30 * 17_280);
+        });
+    }
+
+    #[test]
+    fn test_vested_amount_across_schedule() {
+        let terms = types::VestingTerms {
+            start: 1_000,
+            cliff: 1_250,
+            end: 2_000,
+        };
+        let amount = 100_000_000;
+
+        assert_eq!(vesting::vested_amount(amount, &terms, 0), Ok(0));
+        assert_eq!(vesting::vested_amount(amount, &terms, 1_249), Ok(0));
+        // At the cliff everything accrued since `start` vests at once
+        assert_eq!(vesting::vested_amount(amount, &terms, 1_250), Ok(25_000_000));
+        assert_eq!(vesting::vested_amount(amount, &terms, 1_500), Ok(50_000_000));
+        assert_eq!(vesting::vested_amount(amount, &terms, 1_999), Ok(99_900_000));
+        assert_eq!(vesting::vested_amount(amount, &terms, 2_000), Ok(amount));
+        assert_eq!(vesting::vested_amount(amount, &terms, u64::MAX), Ok(amount));
+
+        assert_eq!(
+            vesting::vested_amount(i128::MAX, &terms, 1_500),
+            Err(errors::Error::InvalidAmount)
+        );
+    }
+
+    #[test]
+    fn test_validate_vesting_terms() {
+        let env = Env::default();
+        env.ledger().set_timestamp(1_000);
+
+        let terms = |start, cliff, end| types::VestingTerms { start, cliff, end };
+
+        assert!(vesting::validate_vesting_terms(&env, &terms(900, 1_100, 2_000)).is_ok());
+        assert_eq!(
+            vesting::validate_vesting_terms(&env, &terms(2_000, 2_000, 2_000)),
+            Err(errors::Error::InvalidGiftTerms)
+        );
+        assert_eq!(
+            vesting::validate_vesting_terms(&env, &terms(1_500, 1_200, 2_000)),
+            Err(errors::Error::InvalidGiftTerms)
+        );
+        assert_eq!(
+            vesting::validate_vesting_terms(&env, &terms(1_500, 2_500, 2_000)),
+            Err(errors::Error::InvalidGiftTerms)
+        );
+        assert_eq!(
+            vesting::validate_vesting_terms(&env, &terms(500, 900, 2_000)),
+            Err(errors::Error::InvalidUnlockTime)
+        );
+    }
+
+    #[test]
+    fn test_schedule_arithmetic_overflow() {
+        let env = Env::default();
+        env.ledger().set_timestamp(1_000);
+
+        // The span of the installments overflows before the lock duration is checked
+        assert_eq!(
+            schedule::validate_schedule(&env, 10_000_000, u64::MAX / 2, 3, 2_000),
+            Err(errors::Error::InvalidGiftTerms)
+        );
+        assert_eq!(
+            schedule::validate_schedule(&env, 10_000_000, u64::MAX - 1_000, 2, 2_000),
+            Err(errors::Error::InvalidGiftTerms)
+        );
+
+        let schedule = schedule::GiftSchedule {
+            sender: <Address as TestAddress>::generate(&env),
+            recipient_phone_hash: soroban_sdk::BytesN::from_array(&env, &[0u8; 32]),
+            amount: i128::MAX / 2,
+            interval: u64::MAX / 2,
+            count: 3,
+            first_unlock: 2_000,
+            gift_ids: soroban_sdk::Vec::new(&env),
+            status: schedule::ScheduleStatus::Active,
+        };
+        assert_eq!(schedule::installment_unlock(&schedule, 1), Ok(u64::MAX / 2 + 2_000));
+        assert_eq!(
+            schedule::installment_unlock(&schedule, 2),
+            Err(errors::Error::InvalidGiftTerms)
+        );
+        assert_eq!(schedule::total_amount(&schedule), Err(errors::Error::InvalidGiftTerms));
+    }
+
+    #[test]
+    fn test_update_recipient_of_deposit_gift_requires_oracle() {
+        let (env, admin, oracle, usdc) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+
+            // Gifts from `deposit_and_create_gift` are sent by the contract itself
+            let gift_id = 1;
+            let gift = types::Gift {
+                sender: env.current_contract_address(),
+                recipient: None,
+                amount: constants::MIN_GIFT_AMOUNT,
+                withdrawn_amount: 0,
+                unlock_timestamp: env.ledger().timestamp() + 3600,
+                recipient_phone_hash: soroban_sdk::BytesN::from_array(&env, &[1u8; 32]),
+                status: types::GiftStatus::Created,
+                kind: types::GiftKind::Standard,
+            };
+            storage::set_gift(&env, gift_id, &gift);
+
+            let new_phone_hash = soroban_sdk::BytesN::from_array(&env, &[7u8; 32]);
+            let result = TimeLockContract::update_recipient(
+                env.clone(),
+                admin.clone(),
+                gift_id,
+                new_phone_hash.clone(),
+            );
+            assert_eq!(result, Err(errors::Error::Unauthorized));
+
+            TimeLockContract::update_recipient(
+                env.clone(),
+                oracle.clone(),
+                gift_id,
+                new_phone_hash.clone(),
+            )
+            .unwrap();
+            let gift = storage::get_gift(&env, gift_id).unwrap();
+            assert_eq!(gift.recipient_phone_hash, new_phone_hash);
+            assert_eq!(storage::get_recipient_changes(&env, gift_id), 1);
+        });
+    }
+
+    #[test]
+    fn test_reject_deposit_gift_refunds_oracle() {
+        use ed25519_dalek::{Signer, SigningKey};
+        use soroban_sdk::{testutils::Events, xdr::ToXdr, TryFromVal};
+
+        let (env, admin, oracle, _) = setup_test_env();
+        let contract_id = env.register(TimeLockContract, ());
+        let usdc = env
+            .register_stellar_asset_contract_v2(<Address as TestAddress>::generate(&env))
+            .address();
+        let usdc_token = soroban_sdk::token::Client::new(&env, &usdc);
+        let amount = constants::MIN_GIFT_AMOUNT;
+        // The anchor delivers the USDC of a deposit straight to the contract
+        soroban_sdk::token::StellarAssetClient::new(&env, &usdc).mint(&contract_id, &amount);
+
+        let oracle_keypair = SigningKey::generate(&mut rand::rngs::OsRng);
+        let recipient_phone_hash = soroban_sdk::BytesN::from_array(&env, &[1u8; 32]);
+        let gift_id = 1u64;
+
+        let mut payload = soroban_sdk::Bytes::new(&env);
+        payload.append(&contract_id.clone().to_xdr(&env));
+        payload.append(&gift_id.to_xdr(&env));
+        payload.append(&recipient_phone_hash.clone().to_xdr(&env));
+        let mut payload_buf = [0u8; 256];
+        let payload_slice = &mut payload_buf[..payload.len() as usize];
+        payload.copy_into_slice(payload_slice);
+        let proof =
+            soroban_sdk::BytesN::from_array(&env, &oracle_keypair.sign(payload_slice).to_bytes());
+
+        env.as_contract(&contract_id, || {
+            initialize_contract(&env, &admin, &oracle, &usdc);
+            storage::set_oracle_auth_key(
+                &env,
+                &soroban_sdk::BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes()),
+            );
+
+            // Gifts from `deposit_and_create_gift` are sent by the contract itself
+            let gift = types::Gift {
+                sender: env.current_contract_address(),
+                recipient: None,
+                amount,
+                withdrawn_amount: 0,
+                unlock_timestamp: env.ledger().timestamp() + 3600,
+                recipient_phone_hash,
+                status: types::GiftStatus::Created,
+                kind: types::GiftKind::Standard,
+            };
+            storage::set_gift(&env, gift_id, &gift);
+            storage::set_total_held(&env, amount);
+            storage::set_total_gifted(&env, amount);
+
+            TimeLockContract::reject_gift(env.clone(), gift_id, proof.clone()).unwrap();
+            // The event names the oracle as the party refunded
+            let (_, _, data) = env.events().all().last().unwrap();
+            let rejected = events::GiftRejected::try_from_val(&env, &data).unwrap();
+            assert_eq!(rejected.sender, contract_id);
+            assert_eq!(rejected.refunded_to, Some(oracle.clone()));
+            let gift = storage::get_gift(&env, gift_id).unwrap();
+            assert_eq!(gift.status, types::GiftStatus::Rejected);
+            assert_eq!(storage::get_total_held(&env), 0);
+            assert_eq!(storage::get_total_gifted(&env), 0);
+        });
+
+        assert_eq!(usdc_token.balance(&oracle), amount);
+        assert_eq!(usdc_token.balance(&contract_id), 0);
+    }
 }
@@ -15,6 +15,20 @@ pub fn allowance(env: &Env, token_address: &Address, from: &Address, spender: &A
     client.allowance(from, spender)
 }
 
+/// Wrapper for approve(spender, amount, expiration_ledger) from this contract
+pub fn approve(
+    env: &Env,
+    token_address: &Address,
+    spender: &Address,
+    amount: i128,
+    expiration_ledger: u32,
+) -> Result<(), Error> {
+    let client = token::Client::new(env, token_address);
+    client.approve(&env.current_contract_address(), spender, &amount, &expiration_ledger);
+
+    Ok(())
+}
+
 /// Wrapper for transfer(to, amount)
 #[allow(dead_code)]
 pub fn transfer(env: &Env, token_address: &Address, to: &Address, amount: i128) -> Result<(), Error> {
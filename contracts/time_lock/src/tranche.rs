@@ -0,0 +1,57 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::types::Tranche;
+use soroban_sdk::{Env, Vec};
+
+/// Validate the tranches of a new milestone gift and return their total
+pub fn validate_tranches(env: &Env, tranches: &Vec<Tranche>) -> Result<i128, Error> {
+    if tranches.is_empty() || tranches.len() > constants::MAX_GIFT_TRANCHES {
+        return Err(Error::InvalidGiftTerms);
+    }
+
+    let current_time = env.ledger().timestamp();
+    let mut total: i128 = 0;
+    let mut previous_unlock = current_time;
+    for tranche in tranches.iter() {
+        // Unlock times must be in the future and strictly increasing
+        if tranche.amount <= 0 || tranche.unlock_timestamp <= previous_unlock {
+            return Err(Error::InvalidGiftTerms);
+        }
+        previous_unlock = tranche.unlock_timestamp;
+        total = total.checked_add(tranche.amount).ok_or(Error::InvalidAmount)?;
+    }
+
+    if previous_unlock - current_time > constants::MAX_LOCK_DURATION {
+        return Err(Error::UnlockTimestampTooFar);
+    }
+    if !(constants::MIN_GIFT_AMOUNT..=constants::MAX_GIFT_AMOUNT).contains(&total) {
+        return Err(Error::InvalidAmount);
+    }
+
+    Ok(total)
+}
+
+/// USDC of the tranches unlocked at `now`
+pub fn unlocked_amount(tranches: &Vec<Tranche>, now: u64) -> i128 {
+    tranches
+        .iter()
+        .filter(|tranche| tranche.unlock_timestamp <= now)
+        .map(|tranche| tranche.amount)
+        .sum()
+}
+
+/// Split tranches into those unlocked at `now` and the USDC of those still locked
+pub fn split_locked(env: &Env, tranches: &Vec<Tranche>, now: u64) -> (Vec<Tranche>, u32, i128) {
+    let mut unlocked = Vec::new(env);
+    let mut locked_count = 0;
+    let mut locked_amount = 0;
+    for tranche in tranches.iter() {
+        if tranche.unlock_timestamp <= now {
+            unlocked.push_back(tranche);
+        } else {
+            locked_count += 1;
+            locked_amount += tranche.amount;
+        }
+    }
+    (unlocked, locked_count, locked_amount)
+}
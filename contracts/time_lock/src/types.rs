@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, BytesN};
+use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -8,6 +8,35 @@ pub enum GiftStatus {
     Unlocked,
     Withdrawn,
     Refunded,
+    PendingSettlement,
+    Collecting,
+    Rejected,
+}
+
+/// Vesting schedule of a streaming gift
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingTerms {
+    pub start: u64, // Vesting starts accruing
+    pub cliff: u64, // Nothing can be withdrawn before this time
+    pub end: u64,   // The whole gift has vested
+}
+
+/// Part of a milestone gift that unlocks on its own date
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tranche {
+    pub unlock_timestamp: u64,
+    pub amount: i128,
+}
+
+/// How a gift's amount becomes available to the recipient
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GiftKind {
+    Standard,               // Everything unlocks at `unlock_timestamp`
+    Vesting(VestingTerms),  // Unlocks linearly between `start` and `end`
+    Tranches(Vec<Tranche>), // Each tranche unlocks at its own time
 }
 
 #[contracttype]
@@ -16,9 +45,11 @@ pub struct Gift {
     pub sender: Address,
     pub recipient: Option<Address>,
     pub amount: i128,
+    pub withdrawn_amount: i128,
     pub unlock_timestamp: u64,
     pub recipient_phone_hash: BytesN<32>,
     pub status: GiftStatus,
+    pub kind: GiftKind,
 }
 
 #[contracttype]
@@ -26,4 +57,26 @@ pub struct Gift {
 pub struct PriceCache {
     pub rate: i128,
     pub timestamp: u64,
+    pub ledger: u32,
+}
+
+/// Bank withdrawal awaiting confirmation from the anchor
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Settlement {
+    pub anchor_address: Address,
+    pub amount_usdc: i128,
+    pub fee_amount: i128,
+    pub anchor_fee: i128, // Part of `amount_usdc` paid to the anchor as USDC, not swapped
+    pub currency: String,
+    pub expected_amount_out: i128,
+    pub min_amount_out: i128,
+    pub memo: String,
+    pub initiated_at: u64,
+    pub nonce: u64,            // Unique per settlement; part of what anchors sign
+    pub payout_asset: Address, // Asset escrowed for the anchor: the payout token, or USDC
+    pub payout_amount: i128,   // Amount of `payout_asset` the anchor receives on confirmation
+    pub top_up_usdc: i128,     // Insurance reserve USDC committed, debited on confirmation
+    pub top_up_amount: i128,   // Part of `payout_amount` bought with `top_up_usdc`
+    pub held: bool,            // Failed or expired, but the payout could not be swapped back yet
 }
@@ -0,0 +1,39 @@
+use crate::constants;
+use crate::errors::Error;
+use crate::types::VestingTerms;
+use soroban_sdk::Env;
+
+/// Validate the terms of a new vesting gift
+pub fn validate_vesting_terms(env: &Env, terms: &VestingTerms) -> Result<(), Error> {
+    if terms.start >= terms.end || terms.cliff < terms.start || terms.cliff > terms.end {
+        return Err(Error::InvalidGiftTerms);
+    }
+
+    let current_time = env.ledger().timestamp();
+    if terms.cliff <= current_time {
+        return Err(Error::InvalidUnlockTime);
+    }
+    if terms.end - current_time > constants::MAX_LOCK_DURATION {
+        return Err(Error::UnlockTimestampTooFar);
+    }
+
+    Ok(())
+}
+
+/// USDC of `amount` vested at `now`: nothing before the cliff, then growing
+/// linearly from `start` until the whole amount has vested at `end`
+pub fn vested_amount(amount: i128, terms: &VestingTerms, now: u64) -> Result<i128, Error> {
+    if now < terms.cliff {
+        return Ok(0);
+    }
+    if now >= terms.end {
+        return Ok(amount);
+    }
+
+    let elapsed = (now - terms.start) as i128;
+    let duration = (terms.end - terms.start) as i128;
+    amount
+        .checked_mul(elapsed)
+        .and_then(|scaled| scaled.checked_div(duration))
+        .ok_or(Error::InvalidAmount)
+}
@@ -0,0 +1,243 @@
+use crate::anchor;
+use crate::constants;
+use crate::errors::Error;
+use crate::oracle::{self, PriceReading};
+use crate::path_payment::PathDiscovery;
+use crate::settlement;
+use crate::slippage::{self, SlippageConfig};
+use crate::storage;
+use crate::token;
+use crate::tranche;
+use crate::vesting;
+use crate::types::{Gift, GiftKind, GiftStatus};
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+/// How a recipient takes a gift out of the contract
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WithdrawalMethod {
+    Wallet, // USDC to the recipient's address (`withdraw_gift`)
+    Bank,   // Fiat payout through an anchor (`withdraw_to_bank`)
+}
+
+/// Where and how a bank withdrawal is paid out
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BankPayout {
+    pub recipient_bank_details_hash: String, // Memo the anchor matches to the recipient's account
+    pub anchor_address: Address,             // Registered anchor making the payout
+    pub currency: String,                    // Fiat currency code paid out (e.g. "NGN")
+    pub min_amount_out: Option<i128>,        // Recipient's floor, stricter than the global one
+    pub deadline: Option<u64>,               // Latest ledger timestamp to initiate the withdrawal
+}
+
+/// What a withdrawal would pay out if submitted now
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalPreview {
+    pub method: WithdrawalMethod,  // Withdrawal being previewed
+    pub currency: String,          // Payout currency ("USDC" for wallet withdrawals)
+    pub fee_amount: i128,          // Platform fee in USDC
+    pub amount_after_fee: i128,    // USDC paid out or sent to the anchor
+    pub anchor_fee: i128,          // Part of `amount_after_fee` the anchor keeps
+    pub oracle_rate: i128,         // Payout currency per USDC (1000000 = 1.0)
+    pub expected_amount_out: i128, // Payout at the oracle rate
+    pub min_amount_out: i128,      // Slippage floor the payout is held to
+    pub path: Vec<Address>,        // Swap path, empty when no swap is needed
+    pub error: Option<u32>,        // `Error` code of the first failing check, if any
+}
+
+/// Oracle rate and slippage bounds for a bank withdrawal in one currency
+pub struct BankPricing {
+    pub currency_pair: String,
+    pub reading: PriceReading,
+    pub slippage_config: SlippageConfig,
+}
+
+/// Fail unless the gift has been claimed and can be withdrawn
+pub fn ensure_withdrawable(env: &Env, gift: &Gift) -> Result<(), Error> {
+    if gift.status == GiftStatus::Unlocked {
+        return Ok(());
+    }
+    if gift.status == GiftStatus::Created && env.ledger().timestamp() < gift.unlock_timestamp {
+        return Err(Error::NotUnlocked);
+    }
+    Err(Error::InvalidStatus)
+}
+
+/// USDC of the gift not yet paid out
+pub fn remaining_amount(gift: &Gift) -> i128 {
+    gift.amount - gift.withdrawn_amount
+}
+
+/// USDC of the gift the recipient can withdraw now: the remainder, or for vesting
+/// and milestone gifts what has unlocked and not yet been paid out
+pub fn available_amount(env: &Env, gift: &Gift) -> Result<i128, Error> {
+    match &gift.kind {
+        GiftKind::Standard => Ok(remaining_amount(gift)),
+        GiftKind::Vesting(terms) => {
+            let vested = vesting::vested_amount(gift.amount, terms, env.ledger().timestamp())?;
+            Ok(vested - gift.withdrawn_amount)
+        }
+        GiftKind::Tranches(tranches) => {
+            let unlocked = tranche::unlocked_amount(tranches, env.ledger().timestamp());
+            Ok(unlocked - gift.withdrawn_amount)
+        }
+    }
+}
+
+/// Fail unless `amount` is a positive part of what can be withdrawn now
+pub fn validate_withdrawal_amount(env: &Env, gift: &Gift, amount: i128) -> Result<(), Error> {
+    if amount <= 0 || amount > available_amount(env, gift)? {
+        return Err(Error::InvalidAmount);
+    }
+    Ok(())
+}
+
+/// Split a withdrawal into the platform fee and the amount paid out
+pub fn split_fee(amount: i128) -> (i128, i128) {
+    let fee_amount = (amount * constants::GIFT_FEE_BPS as i128) / 10000;
+    (fee_amount, amount - fee_amount)
+}
+
+/// Fail unless the contract holds at least `amount` USDC
+pub fn ensure_contract_balance(env: &Env, amount: i128) -> Result<Address, Error> {
+    let usdc_address = storage::get_usdc_address(env).ok_or(Error::InvalidTokenAddress)?;
+    if token::balance_of(env, &usdc_address, &env.current_contract_address()) < amount {
+        return Err(Error::InsufficientFunds);
+    }
+    Ok(usdc_address)
+}
+
+/// Price a bank withdrawal without writing to storage. The caller records
+/// `reading` with `oracle::record_price` if it goes ahead.
+pub fn price_bank_withdrawal(env: &Env, currency: &String) -> Result<BankPricing, Error> {
+    let currency_pair = oracle::currency_pair(env, currency)?;
+    let oracle_config = storage::get_oracle_config(env).ok_or(Error::OracleUnavailable)?;
+    let reading = oracle::read_price(env, &oracle_config, &currency_pair)?;
+    oracle::ensure_tradable(env, &currency_pair, &reading)?;
+
+    let slippage_config = slippage::config_for_pair(env, &currency_pair)?;
+
+    Ok(BankPricing {
+        currency_pair,
+        reading,
+        slippage_config,
+    })
+}
+
+/// Slippage floor for a payout: the stricter of the pair's slippage bound
+/// and the recipient's own minimum
+pub fn min_amount_out(
+    config: &SlippageConfig,
+    expected_amount_out: i128,
+    recipient_min: Option<i128>,
+) -> i128 {
+    let slippage_min_out =
+        (expected_amount_out * (10000 - config.max_slippage_bps as i128)) / 10000;
+    match recipient_min {
+        Some(recipient_min) if recipient_min > slippage_min_out => recipient_min,
+        _ => slippage_min_out,
+    }
+}
+
+/// Check the contract can fund a bank withdrawal and route the `payout_usdc`
+/// of it not kept by the anchor as its fee
+pub fn route_bank_withdrawal(
+    env: &Env,
+    currency: &String,
+    amount_after_fee: i128,
+    payout_usdc: i128,
+    expected_amount_out: i128,
+    min_amount_out: i128,
+) -> Result<Option<PathDiscovery>, Error> {
+    // Only escrow USDC the contract actually holds
+    let usdc_address = ensure_contract_balance(env, amount_after_fee)?;
+
+    // Fail now rather than at confirmation if the payout cannot be routed
+    settlement::quote_payout(
+        env,
+        &usdc_address,
+        currency,
+        payout_usdc,
+        expected_amount_out,
+        min_amount_out,
+    )
+}
+
+/// Run the withdrawal checks for what can be withdrawn of a gift without mutating state or emitting events
+pub fn preview(
+    env: &Env,
+    gift: &Gift,
+    method: WithdrawalMethod,
+    currency: String,
+    anchor: Option<Address>,
+) -> WithdrawalPreview {
+    let (fee_amount, amount_after_fee) = split_fee(available_amount(env, gift).unwrap_or(0));
+    let mut preview = WithdrawalPreview {
+        method,
+        currency,
+        fee_amount,
+        amount_after_fee,
+        anchor_fee: 0,
+        oracle_rate: 0,
+        expected_amount_out: 0,
+        min_amount_out: 0,
+        path: Vec::new(env),
+        error: None,
+    };
+
+    if let Err(error) = fill_preview(env, gift, anchor, &mut preview) {
+        preview.error = Some(error as u32);
+    }
+
+    preview
+}
+
+fn fill_preview(
+    env: &Env,
+    gift: &Gift,
+    anchor: Option<Address>,
+    preview: &mut WithdrawalPreview,
+) -> Result<(), Error> {
+    ensure_withdrawable(env, gift)?;
+    validate_withdrawal_amount(env, gift, preview.fee_amount + preview.amount_after_fee)?;
+
+    match preview.method {
+        WithdrawalMethod::Wallet => {
+            preview.currency = String::from_str(env, "USDC");
+            preview.oracle_rate = 1_000_000;
+            preview.expected_amount_out = preview.amount_after_fee;
+            preview.min_amount_out = preview.amount_after_fee;
+            ensure_contract_balance(env, preview.amount_after_fee)?;
+        }
+        WithdrawalMethod::Bank => {
+            if let Some(anchor) = anchor {
+                let config = anchor::require_active_anchor(env, &anchor, &preview.currency)?;
+                anchor::validate_payout_limits(&config, preview.amount_after_fee)?;
+                preview.anchor_fee = anchor::calculate_anchor_fee(&config, preview.amount_after_fee);
+            }
+            let payout_usdc = preview.amount_after_fee - preview.anchor_fee;
+
+            let pricing = price_bank_withdrawal(env, &preview.currency)?;
+            preview.oracle_rate = pricing.reading.rate;
+            preview.expected_amount_out = (payout_usdc * pricing.reading.rate) / 1_000_000;
+            preview.min_amount_out =
+                min_amount_out(&pricing.slippage_config, preview.expected_amount_out, None);
+
+            let discovery = route_bank_withdrawal(
+                env,
+                &preview.currency,
+                preview.amount_after_fee,
+                payout_usdc,
+                preview.expected_amount_out,
+                preview.min_amount_out,
+            )?;
+            if let Some(discovery) = discovery {
+                preview.path = discovery.best.path;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,11 +1,263 @@
 #![cfg(test)]
 extern crate std;
 
-use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Bytes, BytesN, Env, String, xdr::ToXdr};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token, vec, Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Vec, xdr::ToXdr,
+};
 use ed25519_dalek::{Signer, SigningKey};
 use rand::rngs::OsRng;
+use zendvo_time_lock::events::GiftCreated;
+use zendvo_time_lock::types::{GiftKind, GiftStatus, PriceCache, Tranche, VestingTerms};
+use zendvo_time_lock::{
+    AnchorConfig, BankPayout, FirmQuote, InstallmentStatus, ScheduleStatus, SignedQuote, SplitShare,
+    WithdrawalMethod,
+};
 use zendvo_time_lock::{TimeLockContract, TimeLockContractClient};
 
+// Mock Soroswap-style router: fixed rate per hop, liquidity is its own balance of the output token
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum MockRouterError {
+    NoPool = 1,
+    InsufficientLiquidity = 2,
+    InsufficientOutputAmount = 3,
+    Expired = 4,
+}
+
+#[contracttype]
+pub enum MockRouterKey {
+    Rate(Address, Address),
+}
+
+#[contract]
+pub struct MockRouter;
+
+#[contractimpl]
+impl MockRouter {
+    pub fn set_rate(env: Env, token_in: Address, token_out: Address, rate: i128) {
+        env.storage()
+            .instance()
+            .set(&MockRouterKey::Rate(token_in, token_out), &rate);
+    }
+
+    pub fn router_get_amounts_out(
+        env: Env,
+        amount_in: i128,
+        path: Vec<Address>,
+    ) -> Result<Vec<i128>, MockRouterError> {
+        let mut amounts = vec![&env, amount_in];
+        let mut amount = amount_in;
+        for i in 0..path.len() - 1 {
+            let rate: i128 = env
+                .storage()
+                .instance()
+                .get(&MockRouterKey::Rate(path.get(i).unwrap(), path.get(i + 1).unwrap()))
+                .ok_or(MockRouterError::NoPool)?;
+            amount = amount * rate / 1_000_000;
+            amounts.push_back(amount);
+        }
+
+        let token_out = token::Client::new(&env, &path.last().unwrap());
+        if token_out.balance(&env.current_contract_address()) < amount {
+            return Err(MockRouterError::InsufficientLiquidity);
+        }
+        Ok(amounts)
+    }
+
+    pub fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, MockRouterError> {
+        if env.ledger().timestamp() > deadline {
+            return Err(MockRouterError::Expired);
+        }
+        let amounts = Self::router_get_amounts_out(env.clone(), amount_in, path.clone())?;
+        let amount_out = amounts.last().unwrap();
+        if amount_out < amount_out_min {
+            return Err(MockRouterError::InsufficientOutputAmount);
+        }
+
+        let router = env.current_contract_address();
+        token::Client::new(&env, &path.get(0).unwrap()).transfer_from(&router, &to, &router, &amount_in);
+        token::Client::new(&env, &path.last().unwrap()).transfer(&router, &to, &amount_out);
+        Ok(amounts)
+    }
+}
+
+/// Registers a mock router quoting USDC->NGN at `rate` with `liquidity` NGN, and wires it into the contract
+fn setup_router(
+    env: &Env,
+    client: &TimeLockContractClient,
+    usdc_address: &Address,
+    rate: i128,
+    liquidity: i128,
+) -> (Address, Address) {
+    let router_id = env.register(MockRouter, ());
+    let ngn_address = env.register_stellar_asset_contract_v2(Address::generate(env)).address();
+    token::StellarAssetClient::new(env, &ngn_address).mint(&router_id, &liquidity);
+    MockRouterClient::new(env, &router_id).set_rate(usdc_address, &ngn_address, &rate);
+
+    client.set_dex_router(&router_id);
+    client.set_payout_token(&ngn(env), &ngn_address);
+    (router_id, ngn_address)
+}
+
+/// Registers an NGN anchor with a 1% fee and 1-500 USDC limits, returning its address and signing key
+fn setup_anchor(env: &Env, client: &TimeLockContractClient) -> (Address, SigningKey) {
+    let mut csprng = OsRng;
+    let anchor_keypair = SigningKey::generate(&mut csprng);
+    let anchor = Address::generate(env);
+    client.set_anchor(
+        &anchor,
+        &AnchorConfig {
+            currencies: vec![env, String::from_str(env, "NGN")],
+            min_payout: 1_000_000,
+            max_payout: 500_000_000,
+            fee_bps: 100,
+            enabled: true,
+            signing_key: BytesN::from_array(env, &anchor_keypair.verifying_key().to_bytes()),
+        },
+    );
+    (anchor, anchor_keypair)
+}
+
+/// Stores a pair's circuit breaker reference price as if an earlier oracle reading had set it
+fn seed_reference_price(env: &Env, client: &TimeLockContractClient, currency_pair: &str, rate: i128) {
+    let key = (Symbol::new(env, "ReferencePrice"), String::from_str(env, currency_pair));
+    let reference = PriceCache { rate, timestamp: env.ledger().timestamp(), ledger: env.ledger().sequence() };
+    env.as_contract(&client.address, || env.storage().instance().set(&key, &reference));
+}
+
+/// Funds the insurance reserve with `amount` USDC from a fresh funder
+fn fund_insurance_reserve(env: &Env, client: &TimeLockContractClient, usdc_address: &Address, amount: i128) {
+    let funder = Address::generate(env);
+    token::StellarAssetClient::new(env, usdc_address).mint(&funder, &amount);
+    token::Client::new(env, usdc_address).approve(&funder, &client.address, &amount, &(env.ledger().sequence() + 100));
+    client.fund_insurance_reserve(&funder, &amount);
+}
+
+fn ngn(env: &Env) -> String {
+    String::from_str(env, "NGN")
+}
+
+/// Bank payout through `anchor` with no recipient floor or deadline
+fn bank_payout(memo: &String, anchor: &Address, currency: &String) -> BankPayout {
+    BankPayout {
+        recipient_bank_details_hash: memo.clone(),
+        anchor_address: anchor.clone(),
+        currency: currency.clone(),
+        min_amount_out: None,
+        deadline: None,
+    }
+}
+
+/// Signs a settlement confirmation the way an anchor would
+fn sign_confirmation(
+    env: &Env,
+    client: &TimeLockContractClient,
+    anchor_keypair: &SigningKey,
+    gift_id: u64,
+    anchor_tx_id: &String,
+) -> BytesN<64> {
+    let settlement = client.get_settlement(&gift_id);
+    let mut payload = Bytes::new(env);
+    payload.append(&client.address.clone().to_xdr(env));
+    payload.append(&gift_id.to_xdr(env));
+    payload.append(&settlement.nonce.to_xdr(env));
+    payload.append(&settlement.amount_usdc.to_xdr(env));
+    payload.append(&settlement.initiated_at.to_xdr(env));
+    payload.append(&anchor_tx_id.clone().to_xdr(env));
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    BytesN::from_array(env, &anchor_keypair.sign(&payload_vec).to_bytes())
+}
+
+/// Signs a firm quote the way an anchor would
+fn sign_quote(env: &Env, anchor_keypair: &SigningKey, quote: FirmQuote) -> SignedQuote {
+    let payload = quote.clone().to_xdr(env);
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    SignedQuote {
+        quote,
+        signature: BytesN::from_array(env, &anchor_keypair.sign(&payload_vec).to_bytes()),
+    }
+}
+
+/// Firm quote for the post-fee amount of a 10 USDC gift
+fn firm_quote(env: &Env, anchor: &Address, gift_id: u64, quote_id: &str, rate: i128) -> FirmQuote {
+    FirmQuote {
+        quote_id: String::from_str(env, quote_id),
+        anchor: anchor.clone(),
+        gift_id,
+        sell_amount: 9_800_000,
+        buy_currency: String::from_str(env, "NGN"),
+        rate,
+        fee: 50_000,
+        expires_at: env.ledger().timestamp() + 60,
+    }
+}
+
+/// Signs a claim attestation the way the oracle would
+fn sign_claim(env: &Env, oracle_keypair: &SigningKey, claimant: &Address, recipient_phone_hash: &BytesN<32>) -> BytesN<64> {
+    let mut payload = Bytes::new(env);
+    payload.append(&claimant.clone().to_xdr(env));
+    payload.append(&recipient_phone_hash.clone().to_xdr(env));
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    BytesN::from_array(env, &oracle_keypair.sign(&payload_vec).to_bytes())
+}
+
+fn sign_rejection(env: &Env, client: &TimeLockContractClient, oracle_keypair: &SigningKey, gift_id: u64, recipient_phone_hash: &BytesN<32>) -> BytesN<64> {
+    let mut payload = Bytes::new(env);
+    payload.append(&client.address.clone().to_xdr(env));
+    payload.append(&gift_id.to_xdr(env));
+    payload.append(&recipient_phone_hash.clone().to_xdr(env));
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    BytesN::from_array(env, &oracle_keypair.sign(&payload_vec).to_bytes())
+}
+
+/// Initializes the contract and creates a gift of `amount` whose unlock time has passed
+fn setup_unlocked_gift(env: &Env, amount: i128, phone_seed: u8) -> (TimeLockContractClient<'_>, Address, u64, SigningKey) {
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(env)).address();
+    client.initialize(&Address::generate(env), &oracle_pk, &Address::generate(env), &usdc_address);
+
+    let sender = Address::generate(env);
+    soroban_sdk::token::StellarAssetClient::new(env, &usdc_address).mint(&sender, &amount);
+    soroban_sdk::token::Client::new(env, &usdc_address).approve(&sender, &contract_id, &amount, &(env.ledger().sequence() + 100));
+
+    let recipient_phone_hash = BytesN::from_array(env, &[phone_seed; 32]);
+    let unlock_time = env.ledger().timestamp() + 100;
+    let gift_id = client.create_gift(&sender, &amount, &unlock_time, &recipient_phone_hash);
+    env.ledger().set_timestamp(unlock_time + 1);
+
+    (client, usdc_address, gift_id, oracle_keypair)
+}
+
+/// Initializes the contract and creates a gift of `amount` that a fresh recipient has already claimed
+fn setup_claimed_gift(env: &Env, amount: i128, phone_seed: u8) -> (TimeLockContractClient<'_>, Address, u64) {
+    let (client, usdc_address, gift_id, oracle_keypair) = setup_unlocked_gift(env, amount, phone_seed);
+
+    let claimant = Address::generate(env);
+    let recipient_phone_hash = BytesN::from_array(env, &[phone_seed; 32]);
+    client.claim_gift(&claimant, &gift_id, &sign_claim(env, &oracle_keypair, &claimant, &recipient_phone_hash));
+
+    (client, usdc_address, gift_id)
+}
+
 #[test]
 fn test_claim_gift() {
     let env = Env::default();
@@ -95,12 +347,42 @@ fn test_withdraw_to_bank_success() {
     env.ledger().set_timestamp(unlock_time + 1);
     client.claim_gift(&claimant, &gift_id, &proof);
 
-    let res = client.try_withdraw_to_bank(&gift_id, &String::from_str(&env, "memo"), &Address::generate(&env));
+    let (router_id, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
     assert!(res.is_ok());
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::PendingSettlement);
+    assert_eq!(client.get_anchor_escrow(&anchor), 9_800_000);
+
+    // The swap runs when the withdrawal starts and its output is escrowed;
+    // the anchor's 1% fee stays in USDC
+    let settlement = client.get_settlement(&gift_id);
+    assert_eq!(settlement.anchor_fee, 98_000);
+    assert_eq!(settlement.expected_amount_out, 9_702_000);
+    assert_eq!(settlement.payout_asset, ngn_address);
+    assert_eq!(settlement.payout_amount, 9_604_980);
+    assert_eq!(soroban_sdk::token::Client::new(&env, &ngn_address).balance(&contract_id), 9_604_980);
+
+    // A later pool move does not change what the anchor is paid
+    MockRouterClient::new(&env, &router_id).set_rate(&usdc_address, &ngn_address, &500_000);
+
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Withdrawn);
+    assert_eq!(client.get_anchor_escrow(&anchor), 0);
+
+    // USDC went into the pool and the anchor received the swap output and its fee
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let ngn_token = soroban_sdk::token::Client::new(&env, &ngn_address);
+    assert_eq!(usdc_token.balance(&router_id), 9_702_000);
+    assert_eq!(usdc_token.balance(&anchor), 98_000);
+    assert_eq!(ngn_token.balance(&anchor), 9_604_980);
+    assert_eq!(ngn_token.balance(&contract_id), 0);
 
-    // Verify internal tracking
+    // Verify internal tracking matches the on-chain balance
     assert_eq!(client.get_total_held(), 0);
     assert!(client.get_total_fees() > 0);
+    assert_eq!(usdc_token.balance(&contract_id), client.get_total_fees());
 }
 
 #[test]
@@ -138,8 +420,9 @@ fn test_withdraw_to_bank_slippage_fail() {
     env.ledger().set_timestamp(unlock_time + 1);
     client.claim_gift(&claimant, &gift_id, &proof);
 
-    let res = client.try_withdraw_to_bank(&gift_id, &String::from_str(&env, "memo"), &Address::generate(&env));
-    assert!(res.is_err());
+    setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &setup_anchor(&env, &client).0, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::SlippageExceeded)));
 }
 
 #[test]
@@ -175,8 +458,10 @@ fn test_withdraw_to_bank_insufficient_liquidity() {
     env.ledger().set_timestamp(unlock_time + 1);
     client.claim_gift(&claimant, &gift_id, &proof);
 
-    let res = client.try_withdraw_to_bank(&gift_id, &String::from_str(&env, "memo"), &Address::generate(&env));
-    assert!(res.is_err());
+    // Pool only holds 100 NGN
+    setup_router(&env, &client, &usdc_address, 990_000, 100_000_000);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &setup_anchor(&env, &client).0, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InsufficientLiquidity)));
 }
 
 #[test]
@@ -199,6 +484,1685 @@ fn test_withdraw_to_bank_invalid_status() {
     let recipient_phone_hash = BytesN::from_array(&env, &[14u8; 32]);
     let unlock_time = env.ledger().timestamp() + 100;
     let gift_id = client.create_gift(&sender, &amount, &unlock_time, &recipient_phone_hash);
-    let res = client.try_withdraw_to_bank(&gift_id, &String::from_str(&env, "h"), &Address::generate(&env));
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "h"), &setup_anchor(&env, &client).0, &ngn(&env)), &None);
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_withdraw_to_bank_without_router_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, gift_id) = setup_claimed_gift(&env, 10_000_000, 15);
+    client.set_payout_token(&ngn(&env), &Address::generate(&env));
+
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &setup_anchor(&env, &client).0, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::RouterNotConfigured)));
+}
+
+#[test]
+fn test_withdraw_to_bank_settles_usdc_to_anchor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 19);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+
+    // No NGN token configured: the anchor receives the post-fee USDC directly
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+
+    assert_eq!(usdc_token.balance(&anchor), 9_800_000);
+    assert_eq!(usdc_token.balance(&client.address), 200_000);
+    assert_eq!(
+        usdc_token.balance(&client.address),
+        client.get_total_held() + client.get_total_fees()
+    );
+}
+
+#[test]
+fn test_withdraw_to_bank_insufficient_contract_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 20);
+
+    // Drain the contract so its balance no longer backs the gift
+    soroban_sdk::token::Client::new(&env, &usdc_address).transfer(&client.address, &Address::generate(&env), &10_000_000);
+
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &setup_anchor(&env, &client).0, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InsufficientFunds)));
+}
+
+#[test]
+fn test_withdraw_to_bank_picks_best_multi_hop_path() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &oracle_pk, &Address::generate(&env), &usdc_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&sender, &amount);
+    soroban_sdk::token::Client::new(&env, &usdc_address).approve(&sender, &contract_id, &amount, &(env.ledger().sequence() + 100));
+
+    let recipient_phone_hash = BytesN::from_array(&env, &[16u8; 32]);
+    let unlock_time = env.ledger().timestamp() + 100;
+    let gift_id = client.create_gift(&sender, &amount, &unlock_time, &recipient_phone_hash);
+
+    let claimant = Address::generate(&env);
+    let mut payload = Bytes::new(&env);
+    payload.append(&claimant.clone().to_xdr(&env));
+    payload.append(&recipient_phone_hash.clone().to_xdr(&env));
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    let proof = BytesN::from_array(&env, &oracle_keypair.sign(&payload_vec).to_bytes());
+    env.ledger().set_timestamp(unlock_time + 1);
+    client.claim_gift(&claimant, &gift_id, &proof);
+
+    // Direct pool pays 0.98, routing through XLM pays 10 * 0.0995 = 0.995
+    let (router_id, ngn_address) = setup_router(&env, &client, &usdc_address, 980_000, 1_000_000_000);
+    let xlm_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let router = MockRouterClient::new(&env, &router_id);
+    router.set_rate(&usdc_address, &xlm_address, &10_000_000);
+    router.set_rate(&xlm_address, &ngn_address, &99_500);
+    client.add_intermediate_asset(&xlm_address);
+    assert_eq!(client.get_intermediate_assets(), vec![&env, xlm_address.clone()]);
+
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+
+    let ngn_token = soroban_sdk::token::Client::new(&env, &ngn_address);
+    assert_eq!(ngn_token.balance(&anchor), 9_653_490);
+
+    client.remove_intermediate_asset(&xlm_address);
+    assert_eq!(client.get_intermediate_assets().len(), 0);
+}
+
+#[test]
+fn test_withdraw_to_bank_recipient_min_amount_out() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 17);
+    let (_, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let memo = String::from_str(&env, "memo");
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+
+    // Pool would pay 9_604_980 after the anchor fee; the recipient asked for more
+    let res = client.try_withdraw_to_bank(&gift_id, &BankPayout { min_amount_out: Some(9_650_000), ..bank_payout(&memo, &anchor, &ngn(&env)) }, &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::SlippageExceeded)));
+
+    // A looser recipient floor cannot relax the global bound
+    client.set_max_slippage(&50);
+    let res = client.try_withdraw_to_bank(&gift_id, &BankPayout { min_amount_out: Some(1), ..bank_payout(&memo, &anchor, &ngn(&env)) }, &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::SlippageExceeded)));
+
+    client.set_max_slippage(&200);
+    client.withdraw_to_bank(&gift_id, &BankPayout { min_amount_out: Some(9_600_000), ..bank_payout(&memo, &anchor, &ngn(&env)) }, &None);
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+    assert_eq!(soroban_sdk::token::Client::new(&env, &ngn_address).balance(&anchor), 9_604_980);
+}
+
+#[test]
+fn test_withdraw_to_bank_blocked_by_circuit_breaker() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 47);
+    setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let (anchor, _) = setup_anchor(&env, &client);
+    let memo = String::from_str(&env, "memo");
+    let pair = String::from_str(&env, "USDC/NGN");
+
+    client.set_circuit_breaker(&pair, &1000, &3600);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, vec![&env, symbol_short!("cb_cfg").into_val(&env)]);
+
+    // The oracle reads 1.0 against a 0.8 reference: 25% beyond the 10% limit
+    seed_reference_price(&env, &client, "USDC/NGN", 800_000);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::CircuitBreakerTripped)));
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+
+    // The failed withdrawal rolled back, so only a price check latches the breaker
+    assert!(!client.is_circuit_breaker_tripped(&pair));
+    client.check_exchange_rate(&pair);
+    assert!(client.is_circuit_breaker_tripped(&pair));
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::CircuitBreakerTripped)));
+
+    client.reset_circuit_breaker(&pair);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::PendingSettlement);
+}
+
+#[test]
+fn test_withdraw_to_bank_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 18);
+    setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let memo = String::from_str(&env, "memo");
+    let (anchor, _) = setup_anchor(&env, &client);
+
+    let deadline = env.ledger().timestamp() + 60;
+    env.ledger().set_timestamp(deadline + 1);
+    let res = client.try_withdraw_to_bank(&gift_id, &BankPayout { deadline: Some(deadline), ..bank_payout(&memo, &anchor, &ngn(&env)) }, &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::DeadlineExceeded)));
+
+    let res = client.try_withdraw_to_bank(&gift_id, &BankPayout { deadline: Some(deadline + 60), ..bank_payout(&memo, &anchor, &ngn(&env)) }, &None);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_settlement_confirmed_by_anchor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 21);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+
+    // Funds stay in the contract until the anchor confirms
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    assert_eq!(usdc_token.balance(&anchor), 0);
+    assert_eq!(client.get_total_fees(), 0);
+    assert_eq!(client.get_settlement(&gift_id).amount_usdc, 9_800_000);
+
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    let signature = sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id);
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &signature);
+    assert_eq!(usdc_token.balance(&anchor), 9_800_000);
+    assert_eq!(client.get_total_fees(), 200_000);
+    assert!(client.try_get_settlement(&gift_id).is_err());
+
+    // Settlement can only be finalized once
+    let res = client.try_confirm_settlement(&gift_id, &anchor_tx_id, &signature);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+    let res = client.try_fail_settlement(&gift_id, &String::from_str(&env, "late"));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+}
+
+#[test]
+fn test_settlement_failed_by_anchor_allows_usdc_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 22);
+    let (anchor, _) = setup_anchor(&env, &client);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+
+    client.fail_settlement(&gift_id, &String::from_str(&env, "bank rejected account"));
+    assert_eq!(env.auths()[0].0, anchor);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+    assert_eq!(client.get_anchor_escrow(&anchor), 0);
+    assert_eq!(client.get_total_fees(), 0);
+
+    // Recipient takes the USDC instead
+    client.withdraw_gift(&gift_id);
+    let recipient = client.get_gift(&gift_id).recipient.unwrap();
+    assert_eq!(soroban_sdk::token::Client::new(&env, &usdc_address).balance(&recipient), 9_800_000);
+}
+
+#[test]
+fn test_failed_swapped_settlement_returns_usdc_to_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 44);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (router_id, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let (anchor, _) = setup_anchor(&env, &client);
+    fund_insurance_reserve(&env, &client, &usdc_address, 1_000_000);
+
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    assert_eq!(client.get_settlement(&gift_id).payout_amount, 9_604_980);
+
+    // 9_604_980 NGN come back as 9_701_029 USDC; the reserve absorbs the 971 lost
+    let router = MockRouterClient::new(&env, &router_id);
+    router.set_rate(&ngn_address, &usdc_address, &1_010_000);
+    client.fail_settlement(&gift_id, &String::from_str(&env, "bank rejected account"));
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, GiftStatus::Unlocked);
+    assert_eq!(gift.amount, 10_000_000);
+    assert_eq!(client.get_insurance_reserve(), 1_000_000 - 971);
+    assert_eq!(client.get_anchor_escrow(&anchor), 0);
+    assert_eq!(soroban_sdk::token::Client::new(&env, &ngn_address).balance(&client.address), 0);
+    assert_eq!(
+        usdc_token.balance(&client.address),
+        client.get_total_held() + client.get_insurance_reserve()
+    );
+}
+
+#[test]
+fn test_failed_settlement_of_tranched_gift_keeps_tranches_payable() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 20_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (router_id, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let (anchor, _) = setup_anchor(&env, &client);
+    fund_insurance_reserve(&env, &client, &usdc_address, 1_000_000);
+
+    let now = env.ledger().timestamp();
+    let recipient_phone_hash = BytesN::from_array(&env, &[46u8; 32]);
+    let tranches = vec![
+        &env,
+        Tranche { unlock_timestamp: now + 100, amount: 10_000_000 },
+        Tranche { unlock_timestamp: now + 200, amount: 10_000_000 },
+    ];
+    let gift_id = client.create_tranched_gift(&sender, &tranches, &recipient_phone_hash);
+    env.ledger().set_timestamp(now + 200);
+    let recipient = Address::generate(&env);
+    client.claim_gift(&recipient, &gift_id, &sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash));
+
+    // The partial payout fails and comes back at a loss
+    client.withdraw_to_bank_partial(&gift_id, &5_000_000, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    MockRouterClient::new(&env, &router_id).set_rate(&ngn_address, &usdc_address, &1_010_000);
+    client.fail_settlement(&gift_id, &String::from_str(&env, "bank rejected account"));
+    assert_eq!(client.get_gift(&gift_id).amount, 20_000_000);
+    assert!(client.get_insurance_reserve() < 1_000_000);
+
+    // Both tranches still pay out in full and the books balance
+    client.withdraw_gift(&gift_id);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.withdrawn_amount, 20_000_000);
+    assert_eq!(gift.status, GiftStatus::Withdrawn);
+    assert_eq!(usdc_token.balance(&recipient), 19_600_000);
+    assert_eq!(client.get_total_held(), 0);
+    assert_eq!(
+        usdc_token.balance(&client.address),
+        client.get_total_fees() + client.get_insurance_reserve()
+    );
+}
+
+#[test]
+fn test_held_settlement_can_be_retried_or_accepted() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 45);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (router_id, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let ngn_token = soroban_sdk::token::Client::new(&env, &ngn_address);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let recipient = client.get_gift(&gift_id).recipient.unwrap();
+    let router = MockRouterClient::new(&env, &router_id);
+    fund_insurance_reserve(&env, &client, &usdc_address, 1_000_000);
+
+    // There is no NGN -> USDC pool yet, so expiring holds the NGN in escrow
+    client.withdraw_to_bank_partial(&gift_id, &5_000_000, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    let payout_amount = client.get_settlement(&gift_id).payout_amount;
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * 24 * 60 * 60);
+    client.expire_settlement(&gift_id);
+    assert!(client.get_settlement(&gift_id).held);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::PendingSettlement);
+    assert_eq!(ngn_token.balance(&client.address), payout_amount);
+
+    // The anchor can no longer confirm it, and retrying needs a pool
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_late");
+    let res = client.try_confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+    assert!(client.try_retry_settlement_release(&gift_id).is_err());
+
+    router.set_rate(&ngn_address, &usdc_address, &1_010_000);
+    client.retry_settlement_release(&gift_id);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, GiftStatus::Unlocked);
+    assert_eq!(gift.amount, 10_000_000);
+    assert_eq!(gift.withdrawn_amount, 0);
+    assert!(client.try_get_settlement(&gift_id).is_err());
+
+    // A pool priced beyond the slippage bound holds the payout too; the
+    // recipient may take the NGN instead of waiting for USDC
+    router.set_rate(&ngn_address, &usdc_address, &900_000);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    let payout_amount = client.get_settlement(&gift_id).payout_amount;
+    let res = client.try_accept_settlement_payout(&gift_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+    client.fail_settlement(&gift_id, &String::from_str(&env, "bank rejected account"));
+    assert!(client.get_settlement(&gift_id).held);
+
+    client.accept_settlement_payout(&gift_id);
+    assert!(env.auths().iter().any(|(address, _)| *address == recipient));
+    assert_eq!(ngn_token.balance(&recipient), payout_amount);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Withdrawn);
+    assert_eq!(client.get_anchor_escrow(&anchor), 0);
+    assert_eq!(client.get_total_held(), 0);
+    assert_eq!(
+        usdc_token.balance(&client.address),
+        client.get_total_fees() + client.get_insurance_reserve()
+    );
+}
+
+#[test]
+fn test_settlement_timeout_allows_retry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 23);
+    let memo = String::from_str(&env, "memo");
+    let (silent_anchor, _) = setup_anchor(&env, &client);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&memo, &silent_anchor, &ngn(&env)), &None);
+
+    let res = client.try_expire_settlement(&gift_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::SettlementNotExpired)));
+
+    // Anyone can expire once the anchor has been silent for the timeout
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * 24 * 60 * 60);
+    client.expire_settlement(&gift_id);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+    assert_eq!(client.get_anchor_escrow(&silent_anchor), 0);
+
+    // Retry through a different anchor
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_2");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+    assert_eq!(soroban_sdk::token::Client::new(&env, &usdc_address).balance(&anchor), 9_800_000);
+    assert_eq!(client.get_total_held(), 0);
+}
+
+#[test]
+fn test_settlement_confirmation_requires_anchor_signature() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, gift_id) = setup_claimed_gift(&env, 10_000_000, 24);
+    let (anchor, _) = setup_anchor(&env, &client);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+
+    // Signed by someone other than the registered anchor key
+    let impostor = SigningKey::generate(&mut OsRng);
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    let res = client.try_confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &impostor, gift_id, &anchor_tx_id));
     assert!(res.is_err());
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::PendingSettlement);
+}
+
+#[test]
+fn test_settlement_confirmation_cannot_be_replayed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 25);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let memo = String::from_str(&env, "memo");
+    client.withdraw_to_bank_partial(&gift_id, &4_000_000, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+
+    let first_tx_id = String::from_str(&env, "anchor_tx_1");
+    let second_tx_id = String::from_str(&env, "anchor_tx_2");
+    let first_signature = sign_confirmation(&env, &client, &anchor_key, gift_id, &first_tx_id);
+    let stale_signature = sign_confirmation(&env, &client, &anchor_key, gift_id, &second_tx_id);
+    client.confirm_settlement(&gift_id, &first_tx_id, &first_signature);
+
+    // An identical second settlement of the same gift, started in the same ledger
+    client.withdraw_to_bank_partial(&gift_id, &4_000_000, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+
+    // The first confirmation cannot be submitted again
+    let res = client.try_confirm_settlement(&gift_id, &first_tx_id, &first_signature);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::ConfirmationAlreadyUsed)));
+
+    // A signature over the first settlement does not confirm the second one
+    let res = client.try_confirm_settlement(&gift_id, &second_tx_id, &stale_signature);
+    assert!(res.is_err());
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::PendingSettlement);
+
+    client.confirm_settlement(&gift_id, &second_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &second_tx_id));
+    assert_eq!(soroban_sdk::token::Client::new(&env, &usdc_address).balance(&anchor), 2 * 3_920_000);
+    assert_eq!(client.get_gift(&gift_id).withdrawn_amount, 8_000_000);
+}
+
+#[test]
+fn test_withdraw_to_bank_requires_registered_anchor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, gift_id) = setup_claimed_gift(&env, 10_000_000, 25);
+    let memo = String::from_str(&env, "memo");
+
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &Address::generate(&env), &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::AnchorNotRegistered)));
+
+    let (anchor, _) = setup_anchor(&env, &client);
+    client.set_anchor_enabled(&anchor, &false);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::AnchorDisabled)));
+
+    // Anchor that only pays out KES
+    let mut config = client.get_anchor(&anchor);
+    config.enabled = true;
+    config.currencies = vec![&env, String::from_str(&env, "KES")];
+    client.set_anchor(&anchor, &config);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::CurrencyNotSupported)));
+
+    // Payout above the anchor's limit
+    config.currencies = vec![&env, String::from_str(&env, "NGN")];
+    config.max_payout = 5_000_000;
+    client.set_anchor(&anchor, &config);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::PayoutOutsideAnchorLimits)));
+}
+
+#[test]
+fn test_get_anchors_for_currency() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, _) = setup_claimed_gift(&env, 10_000_000, 26);
+    let (ngn_anchor, _) = setup_anchor(&env, &client);
+    let (disabled_anchor, _) = setup_anchor(&env, &client);
+    client.set_anchor_enabled(&disabled_anchor, &false);
+
+    let (kes_anchor, _) = setup_anchor(&env, &client);
+    let mut config = client.get_anchor(&kes_anchor);
+    config.currencies = vec![&env, String::from_str(&env, "KES"), String::from_str(&env, "NGN")];
+    client.set_anchor(&kes_anchor, &config);
+
+    assert_eq!(
+        client.get_anchors_for_currency(&String::from_str(&env, "NGN")),
+        vec![&env, ngn_anchor, kes_anchor.clone()]
+    );
+    assert_eq!(
+        client.get_anchors_for_currency(&String::from_str(&env, "KES")),
+        vec![&env, kes_anchor]
+    );
+
+    // Invalid limits are rejected
+    config.min_payout = 10;
+    config.max_payout = 1;
+    let res = client.try_set_anchor(&Address::generate(&env), &config);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidAnchorConfig)));
+}
+
+#[test]
+fn test_withdraw_to_bank_with_firm_quote() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, gift_id) = setup_claimed_gift(&env, 10_000_000, 27);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let memo = String::from_str(&env, "memo");
+
+    // Quoted rate is 1% above the oracle rate, inside the 2% slippage bound
+    let quote = sign_quote(&env, &anchor_key, firm_quote(&env, &anchor, gift_id, "q1", 1_010_000));
+    client.withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &Some(quote.clone()));
+
+    let settlement = client.get_settlement(&gift_id);
+    assert_eq!(settlement.expected_amount_out, 9_847_500);
+    assert_eq!(settlement.anchor_fee, 50_000);
+
+    // A quote ID can only be used once
+    client.fail_settlement(&gift_id, &String::from_str(&env, "bank_rejected"));
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &Some(quote));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::QuoteAlreadyUsed)));
+}
+
+#[test]
+fn test_withdraw_to_bank_rejects_invalid_firm_quotes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, gift_id) = setup_claimed_gift(&env, 10_000_000, 28);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let memo = String::from_str(&env, "memo");
+
+    // Expired
+    let mut quote = firm_quote(&env, &anchor, gift_id, "q1", 1_000_000);
+    quote.expires_at = env.ledger().timestamp();
+    env.ledger().with_mut(|li| li.timestamp += 1);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &Some(sign_quote(&env, &anchor_key, quote)));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::QuoteExpired)));
+
+    // Quoted for a different amount
+    let mut quote = firm_quote(&env, &anchor, gift_id, "q2", 1_000_000);
+    quote.sell_amount = 10_000_000;
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &Some(sign_quote(&env, &anchor_key, quote)));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidQuote)));
+
+    // Rate too far from the oracle
+    let quote = firm_quote(&env, &anchor, gift_id, "q3", 1_050_000);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &Some(sign_quote(&env, &anchor_key, quote)));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::SlippageExceeded)));
+
+    // Signed by someone other than the registered anchor key
+    let impostor = SigningKey::generate(&mut OsRng);
+    let quote = firm_quote(&env, &anchor, gift_id, "q4", 1_000_000);
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &ngn(&env)), &Some(sign_quote(&env, &impostor, quote)));
+    assert!(res.is_err());
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+}
+
+#[test]
+fn test_withdraw_to_bank_in_other_currency() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 29);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let memo = String::from_str(&env, "memo");
+    let kes = String::from_str(&env, "KES");
+
+    // The NGN-only anchor cannot pay out KES
+    let res = client.try_withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &kes), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::CurrencyNotSupported)));
+
+    let mut config = client.get_anchor(&anchor);
+    config.currencies = vec![&env, ngn(&env), kes.clone(), String::from_str(&env, "GHS")];
+    client.set_anchor(&anchor, &config);
+
+    // KES has its own slippage tolerance, which sets the settlement floor
+    client.set_pair_max_slippage(&String::from_str(&env, "USDC/KES"), &500);
+    client.withdraw_to_bank(&gift_id, &bank_payout(&memo, &anchor, &kes), &None);
+
+    let settlement = client.get_settlement(&gift_id);
+    assert_eq!(settlement.currency, kes);
+    assert_eq!(settlement.expected_amount_out, 9_702_000);
+    assert_eq!(settlement.min_amount_out, 9_216_900);
+
+    // No KES token registered, so the anchor receives USDC, its fee included
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+    assert_eq!(soroban_sdk::token::Client::new(&env, &usdc_address).balance(&anchor), 9_800_000);
+}
+
+#[test]
+fn test_preview_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 30);
+    let (_, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+
+    let preview = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Bank, &ngn(&env), &None);
+    assert_eq!(preview.error, None);
+    assert_eq!(preview.fee_amount, 200_000);
+    assert_eq!(preview.amount_after_fee, 9_800_000);
+    assert_eq!(preview.oracle_rate, 1_000_000);
+    assert_eq!(preview.expected_amount_out, 9_800_000);
+    assert_eq!(preview.min_amount_out, 9_604_000);
+    assert_eq!(preview.path, vec![&env, usdc_address.clone(), ngn_address.clone()]);
+
+    // Through an anchor its fee comes off before the payout is priced
+    let (anchor, _) = setup_anchor(&env, &client);
+    let preview = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Bank, &ngn(&env), &Some(anchor.clone()));
+    assert_eq!(preview.error, None);
+    assert_eq!(preview.anchor_fee, 98_000);
+    assert_eq!(preview.expected_amount_out, 9_702_000);
+    assert_eq!(preview.min_amount_out, 9_507_960);
+    let res = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Bank, &String::from_str(&env, "KES"), &Some(anchor));
+    assert_eq!(res.error, Some(zendvo_time_lock::errors::Error::CurrencyNotSupported as u32));
+
+    let preview = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Wallet, &ngn(&env), &None);
+    assert_eq!(preview.error, None);
+    assert_eq!(preview.currency, String::from_str(&env, "USDC"));
+    assert_eq!(preview.expected_amount_out, 9_800_000);
+
+    // Pool rate below the slippage floor
+    setup_router(&env, &client, &usdc_address, 950_000, 1_000_000_000);
+    let preview = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Bank, &ngn(&env), &None);
+    assert_eq!(preview.error, Some(zendvo_time_lock::errors::Error::SlippageExceeded as u32));
+
+    client.set_oracle_paused(&true);
+    let preview = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Bank, &ngn(&env), &None);
+    assert_eq!(preview.error, Some(zendvo_time_lock::errors::Error::OraclePaused as u32));
+    assert_eq!(preview.fee_amount, 200_000);
+
+    // Preview never changes the gift
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+}
+
+#[test]
+fn test_preview_withdrawal_before_unlock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &BytesN::from_array(&env, &[0u8; 32]), &Address::generate(&env), &usdc_address);
+
+    let sender = Address::generate(&env);
+    let amount = 10_000_000;
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&sender, &amount);
+    soroban_sdk::token::Client::new(&env, &usdc_address).approve(&sender, &contract_id, &amount, &(env.ledger().sequence() + 100));
+    let gift_id = client.create_gift(&sender, &amount, &(env.ledger().timestamp() + 100), &BytesN::from_array(&env, &[31u8; 32]));
+
+    let preview = client.preview_withdrawal(&gift_id, &WithdrawalMethod::Wallet, &ngn(&env), &None);
+    assert_eq!(preview.error, Some(zendvo_time_lock::errors::Error::NotUnlocked as u32));
+    assert_eq!(client.try_withdraw_gift(&gift_id), Err(Ok(zendvo_time_lock::errors::Error::NotUnlocked)));
+}
+
+#[test]
+fn test_insurance_reserve_tops_up_swap_shortfall() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 32);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (_, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+
+    // Half of every fee goes to the reserve, at most 0.05 USDC per payout
+    client.set_insurance_config(&5000, &50_000);
+    let funder = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&funder, &1_000_000);
+    usdc_token.approve(&funder, &client.address, &1_000_000, &(env.ledger().sequence() + 100));
+    client.fund_insurance_reserve(&funder, &1_000_000);
+    assert!(env.auths().iter().any(|(address, _)| *address == funder));
+    assert_eq!(client.get_insurance_reserve(), 1_000_000);
+
+    // Pool pays 9_604_980 NGN against 9_702_000 expected: 97_020 short, capped at 50_000
+    // USDC, which the reserve swaps into 49_500 NGN added to the payout
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    let settlement = client.get_settlement(&gift_id);
+    assert_eq!(settlement.payout_amount, 9_654_480);
+    assert_eq!(settlement.top_up_usdc, 50_000);
+
+    // The top-up is committed but only debited once the anchor confirms
+    assert_eq!(client.get_insurance_reserve(), 1_000_000);
+    let res = client.try_withdraw_insurance_reserve(&Address::generate(&env), &1_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InsufficientFunds)));
+
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+
+    assert_eq!(soroban_sdk::token::Client::new(&env, &ngn_address).balance(&anchor), 9_654_480);
+    assert_eq!(usdc_token.balance(&anchor), 98_000);
+    assert_eq!(client.get_total_fees(), 100_000);
+    assert_eq!(client.get_insurance_reserve(), 1_000_000 - 50_000 + 100_000);
+    assert_eq!(
+        usdc_token.balance(&client.address),
+        client.get_total_held() + client.get_total_fees() + client.get_insurance_reserve()
+    );
+
+    let to = Address::generate(&env);
+    let res = client.try_withdraw_insurance_reserve(&to, &2_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InsufficientFunds)));
+    client.withdraw_insurance_reserve(&to, &1_050_000);
+    assert_eq!(usdc_token.balance(&to), 1_050_000);
+    assert_eq!(client.get_insurance_reserve(), 0);
+
+    let res = client.try_set_insurance_config(&10_001, &0);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidInsuranceConfig)));
+}
+
+#[test]
+fn test_failed_settlement_returns_insurance_top_up() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 45);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (router_id, ngn_address) = setup_router(&env, &client, &usdc_address, 990_000, 1_000_000_000);
+    MockRouterClient::new(&env, &router_id).set_rate(&ngn_address, &usdc_address, &1_010_000);
+    let (anchor, _) = setup_anchor(&env, &client);
+
+    client.set_insurance_config(&5000, &50_000);
+    let funder = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&funder, &1_000_000);
+    usdc_token.approve(&funder, &client.address, &1_000_000, &(env.ledger().sequence() + 100));
+    client.fund_insurance_reserve(&funder, &1_000_000);
+
+    client.withdraw_to_bank(&gift_id, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    assert_eq!(client.get_settlement(&gift_id).top_up_usdc, 50_000);
+
+    // 9_654_480 NGN come back as 9_751_024 USDC: the reserve gets its 50_000
+    // back and absorbs the 976 lost on the round trip
+    client.fail_settlement(&gift_id, &String::from_str(&env, "bank rejected account"));
+    assert_eq!(client.get_insurance_reserve(), 1_000_000 - 976);
+    assert_eq!(client.get_gift(&gift_id).amount, 10_000_000);
+    assert_eq!(
+        usdc_token.balance(&client.address),
+        client.get_total_held() + client.get_total_fees() + client.get_insurance_reserve()
+    );
+
+    // Nothing stays committed, so the whole reserve can be withdrawn
+    client.withdraw_insurance_reserve(&Address::generate(&env), &(1_000_000 - 976));
+    assert_eq!(client.get_insurance_reserve(), 0);
+}
+
+#[test]
+fn test_partial_withdrawals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 33);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let (anchor, anchor_key) = setup_anchor(&env, &client);
+    let recipient = client.get_gift(&gift_id).recipient.unwrap();
+
+    // 4 USDC to the bank, fee charged on that part only
+    client.withdraw_to_bank_partial(&gift_id, &4_000_000, &bank_payout(&String::from_str(&env, "memo"), &anchor, &ngn(&env)), &None);
+    assert_eq!(client.get_settlement(&gift_id).amount_usdc, 3_920_000);
+    let anchor_tx_id = String::from_str(&env, "anchor_tx_1");
+    client.confirm_settlement(&gift_id, &anchor_tx_id, &sign_confirmation(&env, &client, &anchor_key, gift_id, &anchor_tx_id));
+
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, GiftStatus::Unlocked);
+    assert_eq!(gift.withdrawn_amount, 4_000_000);
+    assert_eq!(usdc_token.balance(&anchor), 3_920_000);
+    assert_eq!(client.get_total_held(), 6_000_000);
+
+    let res = client.try_withdraw_gift_partial(&gift_id, &7_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidAmount)));
+
+    client.withdraw_gift_partial(&gift_id, &1_000_000);
+    assert_eq!(usdc_token.balance(&recipient), 980_000);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+
+    // The rest drains the gift
+    client.withdraw_gift(&gift_id);
+    assert_eq!(usdc_token.balance(&recipient), 980_000 + 4_900_000);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, GiftStatus::Withdrawn);
+    assert_eq!(gift.withdrawn_amount, 10_000_000);
+    assert_eq!(client.get_total_held(), 0);
+    assert_eq!(client.get_total_fees(), 200_000);
+    assert_eq!(usdc_token.balance(&client.address), client.get_total_fees());
+}
+
+#[test]
+fn test_claim_and_withdraw() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id, oracle_keypair) = setup_unlocked_gift(&env, 10_000_000, 34);
+    let claimant = Address::generate(&env);
+    let recipient_phone_hash = BytesN::from_array(&env, &[34u8; 32]);
+
+    // A proof for someone else does not verify
+    let impostor = Address::generate(&env);
+    let res = client.try_claim_and_withdraw(&claimant, &gift_id, &sign_claim(&env, &oracle_keypair, &impostor, &recipient_phone_hash));
+    assert!(res.is_err());
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Created);
+
+    client.claim_and_withdraw(&claimant, &gift_id, &sign_claim(&env, &oracle_keypair, &claimant, &recipient_phone_hash));
+
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.recipient, Some(claimant.clone()));
+    assert_eq!(gift.status, GiftStatus::Withdrawn);
+    assert_eq!(soroban_sdk::token::Client::new(&env, &usdc_address).balance(&claimant), 9_800_000);
+    assert_eq!(client.get_total_fees(), 200_000);
+}
+
+#[test]
+fn test_claim_and_withdraw_to_bank() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, gift_id, oracle_keypair) = setup_unlocked_gift(&env, 10_000_000, 35);
+    let (anchor, _) = setup_anchor(&env, &client);
+    let claimant = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &claimant, &BytesN::from_array(&env, &[35u8; 32]));
+    let memo = String::from_str(&env, "memo");
+
+    // A failing payout check rolls back the claim too
+    let res = client.try_claim_and_withdraw_to_bank(&claimant, &gift_id, &proof, &bank_payout(&memo, &anchor, &String::from_str(&env, "KES")), &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::CurrencyNotSupported)));
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Created);
+
+    client.claim_and_withdraw_to_bank(&claimant, &gift_id, &proof, &bank_payout(&memo, &anchor, &ngn(&env)), &None);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.recipient, Some(claimant));
+    assert_eq!(gift.status, GiftStatus::PendingSettlement);
+    assert_eq!(client.get_settlement(&gift_id).amount_usdc, 9_800_000);
+}
+
+#[test]
+fn test_claim_gift_for_payout_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id, oracle_keypair) = setup_unlocked_gift(&env, 10_000_000, 36);
+    let relayer = Address::generate(&env);
+    let payout_address = Address::generate(&env);
+    let recipient_phone_hash = BytesN::from_array(&env, &[36u8; 32]);
+
+    let mut payload = Bytes::new(&env);
+    payload.append(&relayer.clone().to_xdr(&env));
+    payload.append(&payout_address.clone().to_xdr(&env));
+    payload.append(&recipient_phone_hash.clone().to_xdr(&env));
+    let mut payload_vec = std::vec![0u8; payload.len() as usize];
+    payload.copy_into_slice(&mut payload_vec);
+    let proof = BytesN::from_array(&env, &oracle_keypair.sign(&payload_vec).to_bytes());
+
+    // The proof is bound to the payout address
+    let res = client.try_claim_gift_for(&relayer, &gift_id, &Address::generate(&env), &proof);
+    assert!(res.is_err());
+    // A plain claim proof for the relayer does not cover a payout address
+    let relayer_proof = sign_claim(&env, &oracle_keypair, &relayer, &recipient_phone_hash);
+    let res = client.try_claim_gift_for(&relayer, &gift_id, &payout_address, &relayer_proof);
+    assert!(res.is_err());
+
+    client.claim_gift_for(&relayer, &gift_id, &payout_address, &proof);
+    assert_eq!(client.get_gift(&gift_id).recipient, Some(payout_address.clone()));
+
+    client.withdraw_gift(&gift_id);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    assert_eq!(usdc_token.balance(&payout_address), 9_800_000);
+    assert_eq!(usdc_token.balance(&relayer), 0);
+}
+
+#[test]
+fn test_relay_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, gift_id, oracle_keypair) = setup_unlocked_gift(&env, 10_000_000, 37);
+    let relayer = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let recipient_phone_hash = BytesN::from_array(&env, &[37u8; 32]);
+    let proof = sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash);
+
+    // Only allowlisted relayers may submit claims
+    let res = client.try_relay_claim(&relayer, &recipient, &gift_id, &proof, &0);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::RelayerNotAllowed)));
+
+    client.set_relayer_allowed(&relayer, &true);
+    assert!(client.is_relayer_allowed(&relayer));
+    client.set_max_relay_fee(&100_000);
+    assert_eq!(client.get_max_relay_fee(), 100_000);
+
+    let res = client.try_relay_claim(&relayer, &recipient, &gift_id, &proof, &100_001);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::RelayFeeTooHigh)));
+
+    client.relay_claim(&relayer, &recipient, &gift_id, &proof, &100_000);
+
+    // The recipient authorized the relayed claim, fee included
+    assert!(env.auths().iter().any(|(address, _)| *address == recipient));
+
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.recipient, Some(recipient.clone()));
+    assert_eq!(gift.status, GiftStatus::Unlocked);
+    assert_eq!(gift.withdrawn_amount, 100_000);
+
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    assert_eq!(usdc_token.balance(&relayer), 100_000);
+
+    // The rest of the gift is withdrawn as usual
+    client.withdraw_gift(&gift_id);
+    assert_eq!(usdc_token.balance(&recipient), 9_702_000);
+
+    // Removed relayers can no longer submit claims
+    client.set_relayer_allowed(&relayer, &false);
+    assert!(!client.is_relayer_allowed(&relayer));
+}
+
+#[test]
+fn test_gift_schedule() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &oracle_pk, &Address::generate(&env), &usdc_address);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+
+    let sender = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&sender, &40_000_000);
+    usdc_token.approve(&sender, &contract_id, &40_000_000, &(env.ledger().sequence() + 100));
+
+    let recipient_phone_hash = BytesN::from_array(&env, &[38u8; 32]);
+    let interval = 30 * 24 * 60 * 60;
+    let first_unlock = env.ledger().timestamp() + 100;
+
+    let res = client.try_create_gift_schedule(&sender, &recipient_phone_hash, &10_000_000, &interval, &0, &first_unlock);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidGiftTerms)));
+    // The last installment may not unlock more than 10 years out
+    let res = client.try_create_gift_schedule(&sender, &recipient_phone_hash, &10_000_000, &(12 * interval), &12, &first_unlock);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::UnlockTimestampTooFar)));
+
+    // Four monthly installments, escrowed up front
+    let schedule_id = client.create_gift_schedule(&sender, &recipient_phone_hash, &10_000_000, &interval, &4, &first_unlock);
+    assert_eq!(usdc_token.balance(&sender), 0);
+    assert_eq!(client.get_total_held(), 40_000_000);
+    assert!(client.issue_schedule_installments(&schedule_id).is_empty());
+
+    // The first two installments fall due and are issued as gifts
+    env.ledger().set_timestamp(first_unlock + interval);
+    let issued = client.issue_schedule_installments(&schedule_id);
+    assert_eq!(issued.len(), 2);
+    let first_gift = issued.get(0).unwrap();
+    assert_eq!(client.get_gift(&first_gift).amount, 10_000_000);
+    assert_eq!(client.get_gift(&issued.get(1).unwrap()).unlock_timestamp, first_unlock + interval);
+
+    let recipient = Address::generate(&env);
+    client.claim_gift(&recipient, &first_gift, &sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash));
+    client.withdraw_gift(&first_gift);
+    assert_eq!(usdc_token.balance(&recipient), 9_800_000);
+
+    let installments = client.get_schedule_installments(&schedule_id);
+    assert_eq!(installments.len(), 4);
+    assert_eq!(installments.get(0).unwrap().gift_id, Some(first_gift));
+    assert_eq!(installments.get(0).unwrap().status, InstallmentStatus::Issued(GiftStatus::Withdrawn));
+    assert_eq!(installments.get(1).unwrap().status, InstallmentStatus::Issued(GiftStatus::Created));
+    assert_eq!(installments.get(2).unwrap().status, InstallmentStatus::Scheduled);
+
+    // Cancelling issues the third installment, now due, and refunds the fourth
+    env.ledger().set_timestamp(first_unlock + 2 * interval);
+    assert_eq!(client.cancel_gift_schedule(&schedule_id), 10_000_000);
+    assert_eq!(usdc_token.balance(&sender), 10_000_000);
+    assert_eq!(client.get_gift_schedule(&schedule_id).status, ScheduleStatus::Cancelled);
+
+    let installments = client.get_schedule_installments(&schedule_id);
+    assert_eq!(installments.get(2).unwrap().status, InstallmentStatus::Issued(GiftStatus::Created));
+    assert_eq!(installments.get(3).unwrap().status, InstallmentStatus::Cancelled);
+    assert_eq!(client.get_total_held(), 20_000_000);
+
+    let res = client.try_cancel_gift_schedule(&schedule_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+}
+
+#[test]
+fn test_vesting_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &oracle_pk, &Address::generate(&env), &usdc_address);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+
+    let sender = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&sender, &100_000_000);
+    usdc_token.approve(&sender, &contract_id, &100_000_000, &(env.ledger().sequence() + 100));
+
+    // Vests over 1000s with a 250s cliff
+    let start = env.ledger().timestamp() + 100;
+    let terms = VestingTerms { start, cliff: start + 250, end: start + 1_000 };
+    let recipient_phone_hash = BytesN::from_array(&env, &[39u8; 32]);
+    let gift_id = client.create_vesting_gift(&sender, &100_000_000, &terms, &recipient_phone_hash);
+    assert_eq!(client.get_gift(&gift_id).unlock_timestamp, start + 250);
+
+    let recipient = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash);
+    let res = client.try_claim_gift(&recipient, &gift_id, &proof);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::NotUnlocked)));
+
+    env.ledger().set_timestamp(start + 250);
+    client.claim_gift(&recipient, &gift_id, &proof);
+
+    // A quarter has vested at the cliff; the fee applies to each withdrawal
+    assert_eq!(client.withdraw_vested(&gift_id), 25_000_000);
+    assert_eq!(usdc_token.balance(&recipient), 24_500_000);
+    let res = client.try_withdraw_vested(&gift_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::NotUnlocked)));
+    // Withdrawals cannot reach past what has vested
+    let res = client.try_withdraw_gift_partial(&gift_id, &1);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidAmount)));
+
+    env.ledger().set_timestamp(start + 600);
+    assert_eq!(client.withdraw_vested(&gift_id), 35_000_000);
+    assert_eq!(usdc_token.balance(&recipient), 58_800_000);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+
+    env.ledger().set_timestamp(start + 5_000);
+    assert_eq!(client.withdraw_vested(&gift_id), 40_000_000);
+    assert_eq!(usdc_token.balance(&recipient), 98_000_000);
+
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.withdrawn_amount, 100_000_000);
+    assert_eq!(gift.status, GiftStatus::Withdrawn);
+    assert_eq!(client.get_total_held(), 0);
+}
+
+#[test]
+fn test_withdraw_vested_rejects_standard_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _usdc_address, gift_id) = setup_claimed_gift(&env, 10_000_000, 40);
+    let res = client.try_withdraw_vested(&gift_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidGiftKind)));
+}
+
+#[test]
+fn test_tranched_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &oracle_pk, &Address::generate(&env), &usdc_address);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+
+    let sender = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&sender, &60_000_000);
+    usdc_token.approve(&sender, &contract_id, &60_000_000, &(env.ledger().sequence() + 100));
+
+    let now = env.ledger().timestamp();
+    let year = 365 * 24 * 60 * 60;
+    let tranche = |unlock_timestamp: u64, amount: i128| Tranche { unlock_timestamp, amount };
+    let recipient_phone_hash = BytesN::from_array(&env, &[41u8; 32]);
+
+    // Unlock times must increase
+    let res = client.try_create_tranched_gift(
+        &sender,
+        &vec![&env, tranche(now + year, 10_000_000), tranche(now + 100, 10_000_000)],
+        &recipient_phone_hash,
+    );
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidGiftTerms)));
+
+    let tranches = vec![
+        &env,
+        tranche(now + 100, 20_000_000),
+        tranche(now + year, 20_000_000),
+        tranche(now + 2 * year, 20_000_000),
+    ];
+    let gift_id = client.create_tranched_gift(&sender, &tranches, &recipient_phone_hash);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.amount, 60_000_000);
+    assert_eq!(gift.unlock_timestamp, now + 100);
+
+    env.ledger().set_timestamp(now + 100);
+    let recipient = Address::generate(&env);
+    client.claim_gift(&recipient, &gift_id, &sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash));
+
+    // Only the first tranche can be withdrawn
+    let res = client.try_withdraw_gift_partial(&gift_id, &20_000_001);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidAmount)));
+    client.withdraw_gift(&gift_id);
+    assert_eq!(usdc_token.balance(&recipient), 19_600_000);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+
+    // The second tranche unlocks; cancelling refunds only the third
+    env.ledger().set_timestamp(now + year);
+    assert_eq!(client.cancel_locked_tranches(&gift_id), 20_000_000);
+    assert_eq!(usdc_token.balance(&sender), 20_000_000);
+    let res = client.try_cancel_locked_tranches(&gift_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidGiftTerms)));
+
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.amount, 40_000_000);
+    assert_eq!(gift.kind, GiftKind::Tranches(vec![&env, tranche(now + 100, 20_000_000), tranche(now + year, 20_000_000)]));
+
+    client.withdraw_gift(&gift_id);
+    assert_eq!(usdc_token.balance(&recipient), 39_200_000);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Withdrawn);
+    assert_eq!(client.get_total_held(), 0);
+}
+
+#[test]
+fn test_cancel_locked_tranches_after_withdrawing_unlocked_ones() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 10_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let recipient_phone_hash = BytesN::from_array(&env, &[44u8; 32]);
+    let now = env.ledger().timestamp();
+    let tranches = vec![
+        &env,
+        Tranche { unlock_timestamp: now + 100, amount: 5_000_000 },
+        Tranche { unlock_timestamp: now + 200, amount: 5_000_000 },
+    ];
+    let gift_id = client.create_tranched_gift(&sender, &tranches, &recipient_phone_hash);
+
+    env.ledger().set_timestamp(now + 100);
+    let recipient = Address::generate(&env);
+    client.claim_gift(&recipient, &gift_id, &sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash));
+    client.withdraw_gift(&gift_id);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Unlocked);
+
+    // Nothing is left once the locked tranche is cancelled
+    assert_eq!(client.cancel_locked_tranches(&gift_id), 5_000_000);
+    assert_eq!(usdc_token.balance(&sender), 5_000_000);
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.amount, gift.withdrawn_amount);
+    assert_eq!(gift.status, GiftStatus::Withdrawn);
+    assert_eq!(client.get_total_held(), 0);
+}
+
+#[test]
+fn test_group_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &oracle_pk, &Address::generate(&env), &usdc_address);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let usdc_admin = soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address);
+
+    let organizer = Address::generate(&env);
+    let friends = [Address::generate(&env), Address::generate(&env)];
+    for friend in friends.iter() {
+        usdc_admin.mint(friend, &10_000_000);
+        usdc_token.approve(friend, &contract_id, &10_000_000, &(env.ledger().sequence() + 100));
+    }
+
+    let now = env.ledger().timestamp();
+    let recipient_phone_hash = BytesN::from_array(&env, &[42u8; 32]);
+    let gift_id = client.open_group_gift(&organizer, &(now + 200), &(now + 100), &recipient_phone_hash);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Collecting);
+
+    client.contribute(&gift_id, &friends[0], &4_000_000);
+    client.contribute(&gift_id, &friends[1], &3_000_000);
+    client.contribute(&gift_id, &friends[0], &1_000_000);
+    assert_eq!(client.get_contribution(&gift_id, &friends[0]), 5_000_000);
+    assert_eq!(client.get_group_gift(&gift_id).contributors.len(), 2);
+    assert_eq!(client.get_gift(&gift_id).amount, 8_000_000);
+
+    // A collecting gift cannot be claimed
+    env.ledger().set_timestamp(now + 200);
+    let recipient = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash);
+    let res = client.try_claim_gift(&recipient, &gift_id, &proof);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+
+    // Contributions close at the deadline, after which anyone can close the gift
+    let res = client.try_contribute(&gift_id, &friends[1], &1_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::DeadlineExceeded)));
+    client.close_group_gift(&gift_id);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Created);
+
+    client.claim_gift(&recipient, &gift_id, &proof);
+    client.withdraw_gift(&gift_id);
+    assert_eq!(usdc_token.balance(&recipient), 7_840_000);
+}
+
+#[test]
+fn test_cancel_group_gift_refunds_contributors() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, _gift_id, _oracle_keypair) = setup_unlocked_gift(&env, 5_000_000, 43);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let usdc_admin = soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address);
+
+    let organizer = Address::generate(&env);
+    let friends = [Address::generate(&env), Address::generate(&env)];
+    for friend in friends.iter() {
+        usdc_admin.mint(friend, &10_000_000);
+        usdc_token.approve(friend, &client.address, &10_000_000, &(env.ledger().sequence() + 100));
+    }
+
+    let now = env.ledger().timestamp();
+    let recipient_phone_hash = BytesN::from_array(&env, &[43u8; 32]);
+    let gift_id = client.open_group_gift(&organizer, &(now + 200), &(now + 100), &recipient_phone_hash);
+    client.contribute(&gift_id, &friends[0], &2_000_000);
+    client.contribute(&gift_id, &friends[1], &1_000_000);
+
+    // Too little was raised to close the gift
+    let res = client.try_close_group_gift(&gift_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidAmount)));
+
+    assert_eq!(client.cancel_group_gift(&gift_id), 3_000_000);
+    assert_eq!(usdc_token.balance(&friends[0]), 10_000_000);
+    assert_eq!(usdc_token.balance(&friends[1]), 10_000_000);
+    assert_eq!(client.get_contribution(&gift_id, &friends[0]), 0);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Refunded);
+    assert_eq!(client.get_total_held(), 5_000_000);
+
+    let res = client.try_contribute(&gift_id, &friends[0], &1_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+}
+
+#[test]
+fn test_anyone_can_cancel_group_gift_after_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, _gift_id, _oracle_keypair) = setup_unlocked_gift(&env, 5_000_000, 48);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let friend = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&friend, &2_000_000);
+    usdc_token.approve(&friend, &client.address, &2_000_000, &(env.ledger().sequence() + 100));
+
+    let organizer = Address::generate(&env);
+    let now = env.ledger().timestamp();
+    let gift_id = client.open_group_gift(&organizer, &(now + 200), &(now + 100), &BytesN::from_array(&env, &[48u8; 32]));
+    client.contribute(&gift_id, &friend, &2_000_000);
+
+    // Before the deadline cancelling needs the organizer
+    env.set_auths(&[]);
+    assert!(client.try_cancel_group_gift(&gift_id).is_err());
+
+    // The organizer never closed or cancelled it; once the deadline passes anyone can
+    env.ledger().set_timestamp(now + 101);
+    assert_eq!(client.cancel_group_gift(&gift_id), 2_000_000);
+    assert!(env.auths().is_empty());
+    assert_eq!(usdc_token.balance(&friend), 2_000_000);
+    assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Refunded);
+}
+
+#[test]
+fn test_split_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(&env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(&env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.initialize(&Address::generate(&env), &oracle_pk, &Address::generate(&env), &usdc_address);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+
+    let sender = Address::generate(&env);
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&sender, &30_000_001);
+    usdc_token.approve(&sender, &contract_id, &30_000_001, &(env.ledger().sequence() + 100));
+
+    let now = env.ledger().timestamp();
+    let hashes = [
+        BytesN::from_array(&env, &[44u8; 32]),
+        BytesN::from_array(&env, &[45u8; 32]),
+        BytesN::from_array(&env, &[46u8; 32]),
+    ];
+    let share = |index: usize, share_bps: u32| SplitShare {
+        recipient_phone_hash: hashes[index].clone(),
+        share_bps,
+    };
+
+    // Shares must add up to the whole gift
+    let res = client.try_create_split_gift(
+        &sender,
+        &30_000_001,
+        &(now + 100),
+        &(now + 1_000),
+        &vec![&env, share(0, 5000), share(1, 4000)],
+    );
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidGiftTerms)));
+
+    let split_id = client.create_split_gift(
+        &sender,
+        &30_000_001,
+        &(now + 100),
+        &(now + 1_000),
+        &vec![&env, share(0, 5000), share(1, 3000), share(2, 2000)],
+    );
+    let split = client.get_split_gift(&split_id);
+    assert_eq!(split.gift_ids.len(), 3);
+    assert_eq!(usdc_token.balance(&sender), 0);
+
+    let gift_ids = [
+        split.gift_ids.get(0).unwrap(),
+        split.gift_ids.get(1).unwrap(),
+        split.gift_ids.get(2).unwrap(),
+    ];
+    assert_eq!(client.get_gift(&gift_ids[0]).amount, 15_000_000);
+    assert_eq!(client.get_gift(&gift_ids[1]).amount, 9_000_000);
+    // The last share takes the rounding remainder
+    assert_eq!(client.get_gift(&gift_ids[2]).amount, 6_000_001);
+
+    // Each recipient claims their own share with their own attestation
+    env.ledger().set_timestamp(now + 100);
+    let first = Address::generate(&env);
+    let res = client.try_claim_gift(&first, &gift_ids[1], &sign_claim(&env, &oracle_keypair, &first, &hashes[0]));
+    assert!(res.is_err());
+    client.claim_gift(&first, &gift_ids[0], &sign_claim(&env, &oracle_keypair, &first, &hashes[0]));
+    client.withdraw_gift(&gift_ids[0]);
+    assert_eq!(usdc_token.balance(&first), 14_700_000);
+
+    // Shares can be claimed up to and including the claim deadline
+    env.ledger().set_timestamp(now + 1_000);
+    let second = Address::generate(&env);
+    client.claim_gift(&second, &gift_ids[1], &sign_claim(&env, &oracle_keypair, &second, &hashes[1]));
+    client.withdraw_gift(&gift_ids[1]);
+    assert!(!client.is_split_gift_settled(&split_id));
+
+    // The third share is never claimed and goes back to the sender after the deadline
+    let res = client.try_refund_unclaimed_shares(&split_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::ClaimPeriodNotOver)));
+    env.ledger().set_timestamp(now + 1_001);
+    let third = Address::generate(&env);
+    let res = client.try_claim_gift(&third, &gift_ids[2], &sign_claim(&env, &oracle_keypair, &third, &hashes[2]));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::DeadlineExceeded)));
+    assert_eq!(client.refund_unclaimed_shares(&split_id), 6_000_001);
+    assert_eq!(usdc_token.balance(&sender), 6_000_001);
+    assert_eq!(client.get_gift(&gift_ids[2]).status, GiftStatus::Refunded);
+
+    assert!(client.is_split_gift_settled(&split_id));
+    assert_eq!(client.get_total_held(), 0);
+}
+
+/// Initializes the contract and funds `sender` with `amount` USDC approved for the contract
+fn setup_funded_sender(env: &Env, amount: i128) -> (TimeLockContractClient<'_>, Address, Address, SigningKey) {
+    let mut csprng = OsRng;
+    let oracle_keypair = SigningKey::generate(&mut csprng);
+    let oracle_pk = BytesN::from_array(env, &oracle_keypair.verifying_key().to_bytes());
+    let contract_id = env.register(TimeLockContract, ());
+    let client = TimeLockContractClient::new(env, &contract_id);
+
+    let usdc_address = env.register_stellar_asset_contract_v2(Address::generate(env)).address();
+    client.initialize(&Address::generate(env), &oracle_pk, &Address::generate(env), &usdc_address);
+
+    let sender = Address::generate(env);
+    soroban_sdk::token::StellarAssetClient::new(env, &usdc_address).mint(&sender, &amount);
+    soroban_sdk::token::Client::new(env, &usdc_address).approve(&sender, &contract_id, &amount, &(env.ledger().sequence() + 100));
+
+    (client, usdc_address, sender, oracle_keypair)
+}
+
+#[test]
+fn test_red_envelope_random_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, _oracle_keypair) = setup_funded_sender(&env, 100_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let expires_at = env.ledger().timestamp() + 1_000;
+
+    // The minimum share must fit every claimer
+    let res = client.try_create_red_envelope(&sender, &100_000_000, &4, &30_000_000, &None, &expires_at);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidGiftTerms)));
+
+    let envelope_id = client.create_red_envelope(&sender, &100_000_000, &4, &5_000_000, &None, &expires_at);
+
+    let mut drawn = 0;
+    let mut claimants = std::vec::Vec::new();
+    for _ in 0..4 {
+        let claimant = Address::generate(&env);
+        let gift_id = client.claim_red_envelope(&envelope_id, &claimant, &None, &None);
+        let created = env
+            .events()
+            .all()
+            .iter()
+            .find(|(_, topics, _)| *topics == vec![&env, Symbol::new(&env, "gift_created").into_val(&env)])
+            .map(|(_, _, data)| GiftCreated::try_from_val(&env, &data).unwrap())
+            .unwrap();
+        let gift = client.get_gift(&gift_id);
+        assert_eq!((created.gift_id, created.amount), (gift_id, gift.amount));
+        assert!(gift.amount >= 5_000_000);
+        assert_eq!(gift.recipient, Some(claimant.clone()));
+        assert_eq!(gift.status, GiftStatus::Unlocked);
+        drawn += gift.amount;
+        claimants.push((claimant, gift_id));
+
+        let res = client.try_claim_red_envelope(&envelope_id, &claimants[0].0, &None, &None);
+        assert!(res.is_err());
+    }
+
+    // The last claimer takes the remainder, so the whole pot is drawn
+    assert_eq!(drawn, 100_000_000);
+    let envelope = client.get_red_envelope(&envelope_id);
+    assert_eq!(envelope.remaining, 0);
+    assert_eq!(envelope.claimers.len(), 4);
+
+    let res = client.try_claim_red_envelope(&envelope_id, &Address::generate(&env), &None, &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::RedEnvelopeExhausted)));
+
+    // Shares are withdrawn like any other gift
+    let (claimant, gift_id) = &claimants[0];
+    let share = client.get_gift(gift_id).amount;
+    client.withdraw_gift(gift_id);
+    assert_eq!(usdc_token.balance(claimant), share - share * 200 / 10000);
+}
+
+#[test]
+fn test_red_envelope_allowlist_and_refund() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 30_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let expires_at = env.ledger().timestamp() + 1_000;
+
+    let allowed = BytesN::from_array(&env, &[47u8; 32]);
+    let other = BytesN::from_array(&env, &[48u8; 32]);
+    let allowlist = vec![&env, allowed.clone(), BytesN::from_array(&env, &[49u8; 32])];
+    let envelope_id = client.create_red_envelope(&sender, &30_000_000, &3, &5_000_000, &Some(allowlist), &expires_at);
+
+    let claimant = Address::generate(&env);
+    let res = client.try_claim_red_envelope(&envelope_id, &claimant, &None, &None);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidProof)));
+    let proof = sign_claim(&env, &oracle_keypair, &claimant, &other);
+    let res = client.try_claim_red_envelope(&envelope_id, &claimant, &Some(other), &Some(proof));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::Unauthorized)));
+
+    let proof = sign_claim(&env, &oracle_keypair, &claimant, &allowed);
+    let gift_id = client.claim_red_envelope(&envelope_id, &claimant, &Some(allowed.clone()), &Some(proof));
+    let share = client.get_gift(&gift_id).amount;
+    assert_eq!(client.get_gift(&gift_id).recipient_phone_hash, allowed);
+
+    // Each allowlisted phone hash claims once
+    let second = Address::generate(&env);
+    let proof = sign_claim(&env, &oracle_keypair, &second, &allowed);
+    let res = client.try_claim_red_envelope(&envelope_id, &second, &Some(allowed), &Some(proof));
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::AlreadyClaimed)));
+
+    // What is left goes back to the sender after expiry
+    let res = client.try_refund_red_envelope(&envelope_id);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::ClaimPeriodNotOver)));
+    env.ledger().set_timestamp(expires_at + 1);
+    assert_eq!(client.refund_red_envelope(&envelope_id), 30_000_000 - share);
+    assert_eq!(usdc_token.balance(&sender), 30_000_000 - share);
+    assert_eq!(client.get_total_held(), share);
+}
+
+#[test]
+fn test_top_up_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 20_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+
+    let recipient_phone_hash = BytesN::from_array(&env, &[50u8; 32]);
+    let unlock_time = env.ledger().timestamp() + 100;
+    let gift_id = client.create_gift(&sender, &10_000_000, &unlock_time, &recipient_phone_hash);
+
+    // Only the original sender can top up
+    let res = client.try_top_up_gift(&Address::generate(&env), &gift_id, &5_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::Unauthorized)));
+    let res = client.try_top_up_gift(&sender, &gift_id, &1_000_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidAmount)));
+
+    client.top_up_gift(&sender, &gift_id, &5_000_000);
+    assert_eq!(client.get_gift(&gift_id).amount, 15_000_000);
+    assert_eq!(client.get_total_held(), 15_000_000);
+    assert_eq!(usdc_token.balance(&sender), 5_000_000);
+
+    // Claimed gifts can no longer be topped up
+    env.ledger().set_timestamp(unlock_time);
+    let recipient = Address::generate(&env);
+    client.claim_gift(&recipient, &gift_id, &sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash));
+    let res = client.try_top_up_gift(&sender, &gift_id, &5_000_000);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+
+    client.withdraw_gift(&gift_id);
+    assert_eq!(usdc_token.balance(&recipient), 14_700_000);
+}
+
+#[test]
+fn test_update_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 10_000_000);
+    let wrong_hash = BytesN::from_array(&env, &[51u8; 32]);
+    let right_hash = BytesN::from_array(&env, &[52u8; 32]);
+    let unlock_time = env.ledger().timestamp() + 100;
+    let gift_id = client.create_gift(&sender, &10_000_000, &unlock_time, &wrong_hash);
+
+    let res = client.try_update_recipient(&Address::generate(&env), &gift_id, &right_hash);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::Unauthorized)));
+
+    // Each change counts against the per-gift limit
+    client.update_recipient(&sender, &gift_id, &BytesN::from_array(&env, &[53u8; 32]));
+    client.update_recipient(&sender, &gift_id, &wrong_hash);
+    client.update_recipient(&sender, &gift_id, &right_hash);
+    assert_eq!(client.get_recipient_change_count(&gift_id), 3);
+    let res = client.try_update_recipient(&sender, &gift_id, &wrong_hash);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::LimitReached)));
+
+    // Only the new phone hash can claim
+    env.ledger().set_timestamp(unlock_time);
+    let recipient = Address::generate(&env);
+    let res = client.try_claim_gift(&recipient, &gift_id, &sign_claim(&env, &oracle_keypair, &recipient, &wrong_hash));
+    assert!(res.is_err());
+    client.claim_gift(&recipient, &gift_id, &sign_claim(&env, &oracle_keypair, &recipient, &right_hash));
+
+    let res = client.try_update_recipient(&sender, &gift_id, &wrong_hash);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+}
+
+#[test]
+fn test_reject_gift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 20_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let recipient_phone_hash = BytesN::from_array(&env, &[54u8; 32]);
+    let unlock_time = env.ledger().timestamp() + 100;
+    let gift_id = client.create_gift(&sender, &10_000_000, &unlock_time, &recipient_phone_hash);
+    let other_gift_id = client.create_gift(&sender, &10_000_000, &unlock_time, &recipient_phone_hash);
+
+    // The attestation is for one gift and its phone hash
+    let wrong_hash = BytesN::from_array(&env, &[53u8; 32]);
+    let res = client.try_reject_gift(&gift_id, &sign_rejection(&env, &client, &oracle_keypair, gift_id, &wrong_hash));
+    assert!(res.is_err());
+    let res = client.try_reject_gift(&gift_id, &sign_rejection(&env, &client, &oracle_keypair, other_gift_id, &recipient_phone_hash));
+    assert!(res.is_err());
+    // A claim proof is not a rejection attestation
+    let recipient = Address::generate(&env);
+    let claim_proof = sign_claim(&env, &oracle_keypair, &recipient, &recipient_phone_hash);
+    assert!(client.try_reject_gift(&gift_id, &claim_proof).is_err());
+
+    // Anyone can submit it: no address is bound or authorizes the rejection
+    let attestation = sign_rejection(&env, &client, &oracle_keypair, gift_id, &recipient_phone_hash);
+    client.reject_gift(&gift_id, &attestation);
+    assert!(env.auths().is_empty());
+    let gift = client.get_gift(&gift_id);
+    assert_eq!(gift.status, GiftStatus::Rejected);
+    assert_eq!(gift.recipient, None);
+    // Refunded in full, without the platform fee
+    assert_eq!(usdc_token.balance(&sender), 10_000_000);
+    assert_eq!(client.get_total_held(), 10_000_000);
+    assert_eq!(client.get_total_fees(), 0);
+
+    let res = client.try_reject_gift(&gift_id, &attestation);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+    env.ledger().set_timestamp(unlock_time);
+    let res = client.try_claim_gift(&recipient, &gift_id, &claim_proof);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+
+    // Claimed gifts can no longer be rejected
+    client.claim_gift(&recipient, &other_gift_id, &claim_proof);
+    let attestation = sign_rejection(&env, &client, &oracle_keypair, other_gift_id, &recipient_phone_hash);
+    let res = client.try_reject_gift(&other_gift_id, &attestation);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+}
+
+#[test]
+fn test_reject_group_gift_refunds_contributors() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, contributor, oracle_keypair) = setup_funded_sender(&env, 6_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let organizer = Address::generate(&env);
+    let recipient_phone_hash = BytesN::from_array(&env, &[55u8; 32]);
+    let now = env.ledger().timestamp();
+
+    soroban_sdk::token::StellarAssetClient::new(&env, &usdc_address).mint(&organizer, &4_000_000);
+    usdc_token.approve(&organizer, &client.address, &4_000_000, &(env.ledger().sequence() + 100));
+
+    let gift_id = client.open_group_gift(&organizer, &(now + 200), &(now + 100), &recipient_phone_hash);
+    client.contribute(&gift_id, &contributor, &6_000_000);
+    client.close_group_gift(&gift_id);
+    client.top_up_gift(&organizer, &gift_id, &4_000_000);
+    assert_eq!(usdc_token.balance(&organizer), 0);
+
+    // Contributors get their contributions back and the organizer their top-up
+    client.reject_gift(&gift_id, &sign_rejection(&env, &client, &oracle_keypair, gift_id, &recipient_phone_hash));
+    assert_eq!(usdc_token.balance(&contributor), 6_000_000);
+    assert_eq!(usdc_token.balance(&organizer), 4_000_000);
+    assert_eq!(usdc_token.balance(&client.address), 0);
+    assert_eq!(client.get_total_held(), 0);
+}
+
+#[test]
+fn test_reject_gift_of_each_kind() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, usdc_address, sender, oracle_keypair) = setup_funded_sender(&env, 40_000_000);
+    let usdc_token = soroban_sdk::token::Client::new(&env, &usdc_address);
+    let recipient_phone_hash = BytesN::from_array(&env, &[56u8; 32]);
+    let now = env.ledger().timestamp();
+
+    let shares = vec![
+        &env,
+        SplitShare { recipient_phone_hash: recipient_phone_hash.clone(), share_bps: 5_000 },
+        SplitShare { recipient_phone_hash: BytesN::from_array(&env, &[57u8; 32]), share_bps: 5_000 },
+    ];
+    let split_id = client.create_split_gift(&sender, &10_000_000, &(now + 100), &(now + 1_000), &shares);
+    let share_gift = client.get_split_gift(&split_id).gift_ids.get(0).unwrap();
+
+    let schedule_id = client.create_gift_schedule(&sender, &recipient_phone_hash, &10_000_000, &100, &1, &(now + 100));
+    let terms = VestingTerms { start: now + 100, cliff: now + 200, end: now + 1_000 };
+    let vesting_gift = client.create_vesting_gift(&sender, &10_000_000, &terms, &recipient_phone_hash);
+    let tranches = vec![
+        &env,
+        Tranche { unlock_timestamp: now + 100, amount: 5_000_000 },
+        Tranche { unlock_timestamp: now + 200, amount: 5_000_000 },
+    ];
+    let tranched_gift = client.create_tranched_gift(&sender, &tranches, &recipient_phone_hash);
+    assert_eq!(usdc_token.balance(&sender), 0);
+
+    env.ledger().set_timestamp(now + 100);
+    let installment_gift = client.issue_schedule_installments(&schedule_id).get(0).unwrap();
+
+    // Every kind goes back to the sender in full
+    client.reject_gift(&share_gift, &sign_rejection(&env, &client, &oracle_keypair, share_gift, &recipient_phone_hash));
+    assert_eq!(usdc_token.balance(&sender), 5_000_000);
+    client.reject_gift(&installment_gift, &sign_rejection(&env, &client, &oracle_keypair, installment_gift, &recipient_phone_hash));
+    assert_eq!(usdc_token.balance(&sender), 15_000_000);
+    client.reject_gift(&vesting_gift, &sign_rejection(&env, &client, &oracle_keypair, vesting_gift, &recipient_phone_hash));
+    assert_eq!(usdc_token.balance(&sender), 25_000_000);
+    client.reject_gift(&tranched_gift, &sign_rejection(&env, &client, &oracle_keypair, tranched_gift, &recipient_phone_hash));
+    assert_eq!(usdc_token.balance(&sender), 35_000_000);
+
+    // A rejected gift's locked tranches were already refunded
+    let res = client.try_cancel_locked_tranches(&tranched_gift);
+    assert_eq!(res, Err(Ok(zendvo_time_lock::errors::Error::InvalidStatus)));
+    assert_eq!(usdc_token.balance(&sender), 35_000_000);
+
+    for gift_id in [share_gift, installment_gift, vesting_gift, tranched_gift] {
+        assert_eq!(client.get_gift(&gift_id).status, GiftStatus::Rejected);
+    }
+    // Only the other split share is still held
+    assert_eq!(client.get_total_held(), 5_000_000);
+    assert_eq!(client.get_total_fees(), 0);
 }